@@ -0,0 +1,242 @@
+/// A tuple with at least one element, whose first element can be replaced.
+/// See the [`first`] function.
+///
+/// This trait is implemented for tuples of up to 12 elements.
+pub trait TupleFirst {
+    /// The type of the first element.
+    type First;
+
+    /// The tuple type with the first element replaced by a `C`.
+    type WithFirst<C>;
+
+    /// Maps the first element of the tuple, leaving the others unchanged.
+    fn map_first<F, C>(self, f: F) -> Self::WithFirst<C>
+    where
+        F: FnOnce(Self::First) -> C;
+}
+
+/// A tuple with at least two elements, whose first two elements can be
+/// replaced or swapped. See the [`second`], [`both`] and [`swap`] functions.
+///
+/// This trait is implemented for tuples of 2 to 12 elements.
+pub trait TuplePair: TupleFirst {
+    /// The type of the second element.
+    type Second;
+
+    /// The tuple type with the second element replaced by a `C`.
+    type WithSecond<C>;
+
+    /// The tuple type with the first two elements replaced by a `C` and a
+    /// `D`.
+    type WithBoth<C, D>;
+
+    /// The tuple type with the first two elements swapped.
+    type Swapped;
+
+    /// Maps the second element of the tuple, leaving the others unchanged.
+    fn map_second<F, C>(self, f: F) -> Self::WithSecond<C>
+    where
+        F: FnOnce(Self::Second) -> C;
+
+    /// Maps the first two elements of the tuple, leaving the others
+    /// unchanged.
+    fn map_both<F, G, C, D>(self, f: F, g: G) -> Self::WithBoth<C, D>
+    where
+        F: FnOnce(Self::First) -> C,
+        G: FnOnce(Self::Second) -> D;
+
+    /// Swaps the first two elements of the tuple.
+    fn swap(self) -> Self::Swapped;
+}
+
+macro_rules! impl_tuple_first {
+    ( $( $r:ident $idx:tt ),* ) => {
+        impl<A, $( $r, )*> TupleFirst for (A, $( $r, )*) {
+            type First = A;
+            type WithFirst<C> = (C, $( $r, )*);
+
+            fn map_first<F, C>(self, f: F) -> Self::WithFirst<C>
+            where
+                F: FnOnce(A) -> C,
+            {
+                (f(self.0), $( self.$idx, )*)
+            }
+        }
+    };
+}
+
+macro_rules! impl_tuple_pair {
+    ( $( $r:ident $idx:tt ),* ) => {
+        impl_tuple_first!(B 1 $( , $r $idx )*);
+
+        impl<A, B, $( $r, )*> TuplePair for (A, B, $( $r, )*) {
+            type Second = B;
+            type WithSecond<C> = (A, C, $( $r, )*);
+            type WithBoth<C, D> = (C, D, $( $r, )*);
+            type Swapped = (B, A, $( $r, )*);
+
+            fn map_second<F, C>(self, f: F) -> Self::WithSecond<C>
+            where
+                F: FnOnce(B) -> C,
+            {
+                (self.0, f(self.1), $( self.$idx, )*)
+            }
+
+            fn map_both<F, G, C, D>(self, f: F, g: G) -> Self::WithBoth<C, D>
+            where
+                F: FnOnce(A) -> C,
+                G: FnOnce(B) -> D,
+            {
+                (f(self.0), g(self.1), $( self.$idx, )*)
+            }
+
+            fn swap(self) -> Self::Swapped {
+                (self.1, self.0, $( self.$idx, )*)
+            }
+        }
+    };
+}
+
+impl_tuple_first!();
+impl_tuple_pair!();
+impl_tuple_pair!(R2 2);
+impl_tuple_pair!(R2 2, R3 3);
+impl_tuple_pair!(R2 2, R3 3, R4 4);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5, R6 6);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5, R6 6, R7 7);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5, R6 6, R7 7, R8 8);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5, R6 6, R7 7, R8 8, R9 9);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5, R6 6, R7 7, R8 8, R9 9, R10 10);
+impl_tuple_pair!(R2 2, R3 3, R4 4, R5 5, R6 6, R7 7, R8 8, R9 9, R10 10, R11 11);
+
+/// Creates a pipeline stage that maps the first element of a tuple with the
+/// provided function or closure, passing the other elements through
+/// unchanged.
+///
+/// ```
+/// # use pipe::*;
+/// let pipeline = pipe(|s: &str| (s, s.len())).pipe(first(str::to_uppercase));
+/// assert_eq!(pipeline("foo"), ("FOO".to_owned(), 3));
+/// ```
+pub fn first<T, F, C>(f: F) -> impl Fn(T) -> T::WithFirst<C>
+where
+    T: TupleFirst,
+    F: Fn(T::First) -> C,
+{
+    move |t| t.map_first(&f)
+}
+
+/// Creates a pipeline stage that maps the second element of a tuple with the
+/// provided function or closure, passing the other elements through
+/// unchanged.
+///
+/// ```
+/// # use pipe::*;
+/// let pipeline = pipe(|s: &str| (s, s.len(), true)).pipe(second(|n| n * 2));
+/// assert_eq!(pipeline("foo"), ("foo", 6, true));
+/// ```
+pub fn second<T, F, C>(f: F) -> impl Fn(T) -> T::WithSecond<C>
+where
+    T: TuplePair,
+    F: Fn(T::Second) -> C,
+{
+    move |t| t.map_second(&f)
+}
+
+/// Creates a pipeline stage that maps the first element of a tuple with `f`
+/// and the second with `g`, passing any other elements through unchanged.
+///
+/// ```
+/// # use pipe::*;
+/// let pipeline = pipe(|s: &str| s.split_once('=').unwrap())
+///     .pipe(both(str::trim, |v: &str| v.trim().parse::<i32>()));
+/// assert_eq!(pipeline("x = 5"), ("x", Ok(5)));
+/// ```
+pub fn both<T, F, G, C, D>(f: F, g: G) -> impl Fn(T) -> T::WithBoth<C, D>
+where
+    T: TuplePair,
+    F: Fn(T::First) -> C,
+    G: Fn(T::Second) -> D,
+{
+    move |t| t.map_both(&f, &g)
+}
+
+/// Creates a pipeline stage that swaps the first two elements of a tuple.
+///
+/// ```
+/// # use pipe::*;
+/// let pipeline = pipe(|s: &str| (s.len(), s)).pipe(swap());
+/// assert_eq!(pipeline("foo"), ("foo", 3));
+/// ```
+pub fn swap<T>() -> impl Fn(T) -> T::Swapped
+where
+    T: TuplePair,
+{
+    T::swap
+}
+
+/// Creates a pipeline stage that reassociates a nested pair to the right,
+/// turning `((a, b), c)` into `(a, (b, c))`. This lets [`second`] map the
+/// last two values together.
+///
+/// ```
+/// # use pipe::*;
+/// let pipeline = pipe(|n: i32| ((n, n + 1), n + 2))
+///     .pipe(assoc())
+///     .pipe(second(|(b, c)| b * c));
+/// assert_eq!(pipeline(1), (1, 6));
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn assoc<A, B, C>() -> impl Fn(((A, B), C)) -> (A, (B, C)) {
+    |((a, b), c)| (a, (b, c))
+}
+
+/// Creates a pipeline stage that reassociates a nested pair to the left,
+/// turning `(a, (b, c))` into `((a, b), c)`. This is the inverse of
+/// [`assoc`].
+#[allow(clippy::type_complexity)]
+pub fn unassoc<A, B, C>() -> impl Fn((A, (B, C))) -> ((A, B), C) {
+    |(a, (b, c))| ((a, b), c)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_first_and_second_inference() {
+        let pipeline = pipe(|s: &str| (s.len(), s.to_owned()))
+            .pipe(first(|n| n + 1))
+            .pipe(second(|s: String| s + "!"))
+            .pipe(swap());
+        assert_eq!(pipeline("foo"), ("foo!".to_owned(), 4));
+
+        let single = pipe(|n: i32| (n,)).pipe(first(|n| n * 2));
+        assert_eq!(single(2), (4,));
+    }
+
+    #[test]
+    fn test_max_arity() {
+        let pipeline = Pipeline::new(|n: u8| (n, n, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11))
+            .then(both(|a| a + 10, |b| b + 20))
+            .then(swap())
+            .then(second(u32::from));
+        assert_eq!(
+            pipeline.call(1),
+            (21, 11u32, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11)
+        );
+    }
+
+    #[test]
+    fn test_assoc_roundtrip() {
+        let pipeline = Pipeline::new(|s: String| ((s.len(), s.is_empty()), s))
+            .then(assoc())
+            .then(second(swap()))
+            .then(unassoc());
+        assert_eq!(
+            pipeline.call("foo".to_owned()),
+            ((3, "foo".to_owned()), false)
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+/// The `AsyncPipe` trait composes asynchronous pipelines, where stages may be
+/// `async fn`s, async closures or closures returning futures. The composed
+/// pipeline returns a single future that awaits each stage in order.
+///
+/// The futures produced by this trait do not depend on any particular runtime.
+/// They are [`AsyncThen`] and [`SyncThen`] values wrapping the futures of each
+/// stage, so they are `Send` whenever the futures and functions of every stage
+/// are, and can be spawned onto multi-threaded executors.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnOnce(A) -> Fut`, where `Fut` is a future, which includes `async fn`s,
+/// async closures and closures returning futures.
+pub trait AsyncPipe<A, Fut>
+where
+    Fut: Future,
+{
+    /// Wraps an asynchronous function or closure inside the currently
+    /// constructed pipeline. The stage is called with the output of the
+    /// previous stage once it has been awaited.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// # use pipe::__block_on as block_on;
+    /// async fn lookup(id: u32) -> String {
+    ///     format!("user-{id}")
+    /// }
+    ///
+    /// async fn greet(name: String) -> String {
+    ///     format!("hello, {name}")
+    /// }
+    ///
+    /// let greet_user = pipe_async(lookup).async_pipe(greet);
+    /// assert_eq!(block_on(greet_user(7)), "hello, user-7");
+    /// ```
+    fn async_pipe<F, Fut2>(self, f: F) -> impl FnOnce(A) -> AsyncThen<Fut, F, Fut2>
+    where
+        F: FnOnce(Fut::Output) -> Fut2,
+        Fut2: Future;
+
+    /// Wraps a synchronous function or closure inside the currently
+    /// constructed pipeline. The stage is called with the output of the
+    /// previous stage once it has been awaited.
+    fn sync_pipe<F, C>(self, f: F) -> impl FnOnce(A) -> SyncThen<Fut, F>
+    where
+        F: FnOnce(Fut::Output) -> C;
+}
+
+impl<F1, A, Fut> AsyncPipe<A, Fut> for F1
+where
+    F1: FnOnce(A) -> Fut,
+    Fut: Future,
+{
+    fn async_pipe<F2, Fut2>(self, f: F2) -> impl FnOnce(A) -> AsyncThen<Fut, F2, Fut2>
+    where
+        F2: FnOnce(Fut::Output) -> Fut2,
+        Fut2: Future,
+    {
+        |a| AsyncThen {
+            state: AsyncThenState::First(self(a), Some(f)),
+        }
+    }
+
+    fn sync_pipe<F2, C>(self, f: F2) -> impl FnOnce(A) -> SyncThen<Fut, F2>
+    where
+        F2: FnOnce(Fut::Output) -> C,
+    {
+        |a| SyncThen {
+            fut: self(a),
+            f: Some(f),
+        }
+    }
+}
+
+/// The future returned by pipelines extended with [`AsyncPipe::async_pipe`].
+/// It awaits the future of the previous stages, then the future returned by
+/// the new stage.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct AsyncThen<Fut1, F, Fut2> {
+    state: AsyncThenState<Fut1, F, Fut2>,
+}
+
+enum AsyncThenState<Fut1, F, Fut2> {
+    First(Fut1, Option<F>),
+    Second(Fut2),
+    Done,
+}
+
+impl<Fut1, F, Fut2> Future for AsyncThen<Fut1, F, Fut2>
+where
+    Fut1: Future,
+    F: FnOnce(Fut1::Output) -> Fut2,
+    Fut2: Future,
+{
+    type Output = Fut2::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the futures held by the state are never moved. They are only
+        // pinned in place and dropped in place when the state is replaced. The
+        // stage function is not pinned, so it can be moved out of its option.
+        let state = unsafe { &mut self.get_unchecked_mut().state };
+        loop {
+            match state {
+                AsyncThenState::First(fut, f) => {
+                    let b = ready!(unsafe { Pin::new_unchecked(fut) }.poll(cx));
+                    let f = f.take().expect("`AsyncThen` stage already called");
+                    *state = AsyncThenState::Second(f(b));
+                }
+                AsyncThenState::Second(fut) => {
+                    let c = ready!(unsafe { Pin::new_unchecked(fut) }.poll(cx));
+                    *state = AsyncThenState::Done;
+                    return Poll::Ready(c);
+                }
+                AsyncThenState::Done => panic!("`AsyncThen` polled after completion"),
+            }
+        }
+    }
+}
+
+/// The future returned by pipelines extended with [`AsyncPipe::sync_pipe`].
+/// It awaits the future of the previous stages, then calls the new stage with
+/// its output.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SyncThen<Fut, F> {
+    fut: Fut,
+    f: Option<F>,
+}
+
+impl<Fut, F, C> Future for SyncThen<Fut, F>
+where
+    Fut: Future,
+    F: FnOnce(Fut::Output) -> C,
+{
+    type Output = C;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<C> {
+        // SAFETY: the future is pinned in place and never moved. The stage
+        // function is not pinned, so it can be moved out of its option.
+        let this = unsafe { self.get_unchecked_mut() };
+        let b = ready!(unsafe { Pin::new_unchecked(&mut this.fut) }.poll(cx));
+        let f = this.f.take().expect("`SyncThen` polled after completion");
+        Poll::Ready(f(b))
+    }
+}
+
+/// This is a convenience function to start an asynchronous pipeline. See the
+/// documentation for the [`AsyncPipe`] trait for examples.
+///
+/// Any function returning a future can start an asynchronous pipeline,
+/// including synchronous pipelines built with [`pipe`](fn@crate::pipe) whose last
+/// stage returns a future:
+///
+/// ```
+/// # use pipe::*;
+/// # use pipe::__block_on as block_on;
+/// async fn lookup(id: u32) -> String {
+///     format!("user-{id}")
+/// }
+///
+/// let lookup_user = pipe_async(pipe(|id: &str| id.parse().unwrap()).pipe(lookup))
+///     .sync_pipe(|name| name.to_uppercase());
+/// assert_eq!(block_on(lookup_user("7")), "USER-7");
+/// ```
+pub fn pipe_async<F, A, Fut>(f: F) -> impl FnOnce(A) -> Fut
+where
+    F: FnOnce(A) -> Fut,
+    Fut: Future,
+{
+    f
+}
+
+/// Polls a future to completion on the current thread, for examples and tests
+/// that should not depend on a runtime.
+#[doc(hidden)]
+pub fn __block_on<F: Future>(f: F) -> F::Output {
+    let mut f = std::pin::pin!(f);
+    let mut cx = Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let Poll::Ready(out) = f.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{block_on, YieldNow};
+    use crate::*;
+    use std::future::Future;
+
+    async fn double_later(n: i32) -> i32 {
+        YieldNow(false).await;
+        n * 2
+    }
+
+    #[test]
+    fn test_async_pipe_mixed_stages() {
+        let mut order = Vec::new();
+        let pipeline = pipe_async(pipe(|s: &str| s.len() as i32).pipe(double_later))
+            .sync_pipe(|n| {
+                order.push("sync");
+                n + 1
+            })
+            .async_pipe(|n| async move {
+                YieldNow(false).await;
+                n * 10
+            });
+        assert_eq!(block_on(pipeline("foo")), 70);
+        assert_eq!(order, ["sync"]);
+    }
+
+    #[test]
+    fn test_async_pipe_is_lazy() {
+        let mut called = false;
+        let pipeline = pipe_async(double_later).sync_pipe(|n| {
+            called = true;
+            n
+        });
+        let future = pipeline(1);
+        assert_eq!(block_on(future), 2);
+        assert!(called);
+    }
+
+    #[test]
+    fn test_async_pipe_send() {
+        fn spawn<F: Future + Send + 'static>(f: F) -> std::thread::JoinHandle<F::Output>
+        where
+            F::Output: Send,
+        {
+            std::thread::spawn(move || block_on(f))
+        }
+
+        let pipeline = pipe_async(double_later)
+            .sync_pipe(|n| n.to_string())
+            .async_pipe(async |s: String| {
+                YieldNow(false).await;
+                s.len()
+            });
+        assert_eq!(spawn(pipeline(500)).join().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_async_pipe_macro() {
+        let offset = 1;
+        let pipeline = pipe! { async this: i32;
+               double_later(this).await
+            => this + offset
+            => double_later(this).await
+        };
+        assert_eq!(block_on(pipeline(1)), 6);
+        assert_eq!(block_on(pipeline(2)), 10);
+    }
+
+    #[test]
+    fn test_async_pipe_macro_borrowed_capture() {
+        let prefix = String::from("id-");
+        let pipeline = pipe! { async this: u32;
+               format!("{prefix}{this}")
+            => this.len()
+        };
+        assert_eq!(block_on(pipeline(1)), 4);
+        assert_eq!(block_on(pipeline(23)), 5);
+        assert_eq!(prefix, "id-");
+    }
+
+    #[test]
+    fn test_pipe_value_macro_await() {
+        let value =
+            block_on(async { pipe_value!(this = 3 => double_later(this).await => this + 1) });
+        assert_eq!(value, 7);
+    }
+}
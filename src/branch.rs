@@ -0,0 +1,178 @@
+/// A value of one of two types, produced by a [`split`] stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Either<L, R> {
+    /// The value was routed to the left sub-pipeline.
+    Left(L),
+    /// The value was routed to the right sub-pipeline.
+    Right(R),
+}
+
+impl<L, R> Either<L, R> {
+    /// Returns `true` if the value is [`Either::Left`].
+    pub fn is_left(&self) -> bool {
+        matches!(self, Self::Left(_))
+    }
+
+    /// Returns `true` if the value is [`Either::Right`].
+    pub fn is_right(&self) -> bool {
+        matches!(self, Self::Right(_))
+    }
+
+    /// Returns the left value, if any.
+    pub fn left(self) -> Option<L> {
+        match self {
+            Self::Left(l) => Some(l),
+            Self::Right(_) => None,
+        }
+    }
+
+    /// Returns the right value, if any.
+    pub fn right(self) -> Option<R> {
+        match self {
+            Self::Left(_) => None,
+            Self::Right(r) => Some(r),
+        }
+    }
+
+    /// Converts either value into a common type, using the function matching
+    /// the side the value is on.
+    pub fn either<T, F, G>(self, f: F, g: G) -> T
+    where
+        F: FnOnce(L) -> T,
+        G: FnOnce(R) -> T,
+    {
+        match self {
+            Self::Left(l) => f(l),
+            Self::Right(r) => g(r),
+        }
+    }
+}
+
+impl<T> Either<T, T> {
+    /// Returns the value, whichever side it is on.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Left(t) | Self::Right(t) => t,
+        }
+    }
+}
+
+/// Creates a pipeline stage that runs the provided `T -> T` function or
+/// closure only if the predicate holds for its input, and otherwise returns
+/// the input unchanged.
+///
+/// Like the other stage functions, the returned stage implements `Fn`, so it
+/// can be used in any kind of pipeline. The input type usually needs to be
+/// annotated on the predicate.
+///
+/// ```
+/// # use pipe::*;
+/// let abs_then_double = pipe(|n: i32| n)
+///     .pipe(pipe_if(|n: &i32| *n < 0, |n| -n))
+///     .pipe(|n| n * 2);
+/// assert_eq!(abs_then_double(-4), 8);
+/// ```
+pub fn pipe_if<T, P, F>(pred: P, f: F) -> impl Fn(T) -> T
+where
+    P: Fn(&T) -> bool,
+    F: Fn(T) -> T,
+{
+    move |t| if pred(&t) { f(t) } else { t }
+}
+
+/// Creates a pipeline stage that routes its input into one of two
+/// sub-pipelines producing the same output type: `then` if the predicate
+/// holds for the input, and `otherwise` if it does not.
+///
+/// ```
+/// # use pipe::*;
+/// let describe = pipe(|s: &str| s.trim()).pipe(branch(
+///     |s: &&str| s.is_empty(),
+///     |_| "nothing".to_owned(),
+///     |s| format!("{} chars", s.len()),
+/// ));
+/// assert_eq!(describe("  foo "), "3 chars");
+/// ```
+pub fn branch<B, C, P, F, G>(pred: P, then: F, otherwise: G) -> impl Fn(B) -> C
+where
+    P: Fn(&B) -> bool,
+    F: Fn(B) -> C,
+    G: Fn(B) -> C,
+{
+    move |b| if pred(&b) { then(b) } else { otherwise(b) }
+}
+
+/// Creates a pipeline stage that routes its input into one of two
+/// sub-pipelines with different output types: `left` if the predicate holds
+/// for the input, and `right` if it does not. The output is wrapped in the
+/// matching [`Either`] variant.
+///
+/// ```
+/// # use pipe::*;
+/// let parse = pipe(|s: &str| s.trim()).pipe(split(
+///     |s: &&str| s.starts_with('#'),
+///     |s| s.len(),
+///     |s| s.to_uppercase(),
+/// ));
+/// assert_eq!(parse("#ff"), Either::Left(3));
+/// ```
+pub fn split<B, L, R, P, F, G>(pred: P, left: F, right: G) -> impl Fn(B) -> Either<L, R>
+where
+    P: Fn(&B) -> bool,
+    F: Fn(B) -> L,
+    G: Fn(B) -> R,
+{
+    move |b| {
+        if pred(&b) {
+            Either::Left(left(b))
+        } else {
+            Either::Right(right(b))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_pipe_if() {
+        let clamp = Pipeline::new(|s: &str| s.parse::<i32>().unwrap())
+            .then(pipe_if(|n: &i32| *n > 100, |_| 100))
+            .then(pipe_if(|n: &i32| *n < 0, |_| 0));
+        assert_eq!(
+            [clamp.call("150"), clamp.call("-3"), clamp.call("42")],
+            [100, 0, 42]
+        );
+    }
+
+    #[test]
+    fn test_branch_and_split() {
+        let collatz =
+            pipe_fn(|n: u64| n).pipe_fn(branch(|n: &u64| n % 2 == 0, |n| n / 2, |n| 3 * n + 1));
+        assert_eq!([collatz(6), collatz(7)], [3, 22]);
+
+        let classify = pipe_fn(|s: &str| s.trim()).pipe_fn(split(
+            |s: &&str| s.parse::<f64>().is_ok(),
+            |s| s.parse::<f64>().unwrap(),
+            |s| s.to_owned(),
+        ));
+        assert_eq!(classify(" 1.5 "), Either::Left(1.5));
+        assert_eq!(classify("foo"), Either::Right("foo".to_owned()));
+        assert!(classify("2").is_left());
+        assert_eq!(classify("bar").right().as_deref(), Some("bar"));
+        assert_eq!(classify("3").either(|n| n as usize, |s| s.len()), 3);
+    }
+
+    #[test]
+    fn test_branch_macro() {
+        let normalize = pipe! { n: i32;
+               when (n < 0) -n
+            => branch (n % 2 == 0) n / 2, n * 3 + 1
+            => split (n > 10) n.to_string(), n
+        };
+        assert_eq!(normalize(-4), Either::Right(2));
+        assert_eq!(normalize(7), Either::Left("22".to_owned()));
+        assert_eq!(Either::<i32, i32>::Left(1).into_inner(), 1);
+    }
+}
@@ -0,0 +1,363 @@
+use crate::{Stage, StageFn, StageMut};
+use std::fmt;
+use std::ops::RangeBounds;
+
+/// A named stage of a [`Chain`].
+struct Link<T> {
+    name: String,
+    priority: i32,
+    f: Box<dyn Fn(T) -> T>,
+}
+
+/// A chain of `T -> T` stages that can be assembled and rearranged at runtime,
+/// such as a list of normalizers or middleware registered by plugins.
+///
+/// Every stage has a name, used to find it later, and a priority, used by
+/// [`insert_by_priority`](Chain::insert_by_priority) and
+/// [`sort_by_priority`](Chain::sort_by_priority). Stages run in the order they
+/// appear in the chain, and an empty chain returns its input unchanged.
+///
+/// When several stages share a name, methods looking stages up by name act on
+/// the first of them.
+///
+/// ```
+/// # use pipe::*;
+/// let mut normalize = Chain::new()
+///     .then("trim", |s: String| s.trim().to_owned())
+///     .then("lowercase", |s| s.to_lowercase());
+/// normalize.insert_by_priority("collapse", -1, |s| {
+///     s.split_whitespace().collect::<Vec<_>>().join(" ")
+/// });
+///
+/// assert_eq!(normalize.names().collect::<Vec<_>>(), ["collapse", "trim", "lowercase"]);
+/// assert_eq!(normalize.apply("  Hello   World ".to_owned()), "hello world");
+/// ```
+pub struct Chain<T> {
+    links: Vec<Link<T>>,
+}
+
+impl<T> Chain<T> {
+    /// Creates an empty chain.
+    pub fn new() -> Self {
+        Self { links: Vec::new() }
+    }
+
+    /// Adds a stage to the end of the chain, returning the chain. The stage
+    /// is given the same priority as the current last stage, or `0` if the
+    /// chain is empty.
+    pub fn then<F>(mut self, name: impl Into<String>, f: F) -> Self
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        self.push(name, f);
+        self
+    }
+
+    /// Adds a stage to the end of the chain. The stage is given the same
+    /// priority as the current last stage, or `0` if the chain is empty.
+    pub fn push<F>(&mut self, name: impl Into<String>, f: F)
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        let priority = self.links.last().map_or(0, |link| link.priority);
+        self.links.push(Link::new(name, priority, f));
+    }
+
+    /// Inserts a stage at the given index, giving it the priority of the
+    /// stage it is inserted after, or `0` if it is inserted first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert<F>(&mut self, index: usize, name: impl Into<String>, f: F)
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        let priority = index
+            .checked_sub(1)
+            .and_then(|i| self.links.get(i))
+            .map_or(0, |link| link.priority);
+        self.links.insert(index, Link::new(name, priority, f));
+    }
+
+    /// Inserts a stage directly before the stage named `before`. Returns
+    /// `false`, leaving the chain unchanged, if there is no such stage.
+    pub fn insert_before<F>(&mut self, before: &str, name: impl Into<String>, f: F) -> bool
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        match self.position(before) {
+            Some(index) => {
+                let priority = self.links[index].priority;
+                self.links.insert(index, Link::new(name, priority, f));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts a stage directly after the stage named `after`. Returns
+    /// `false`, leaving the chain unchanged, if there is no such stage.
+    pub fn insert_after<F>(&mut self, after: &str, name: impl Into<String>, f: F) -> bool
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        match self.position(after) {
+            Some(index) => {
+                let priority = self.links[index].priority;
+                self.links.insert(index + 1, Link::new(name, priority, f));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts a stage after every stage with a lower or equal priority and
+    /// before the first stage with a higher priority. Stages with lower
+    /// priorities run first.
+    pub fn insert_by_priority<F>(&mut self, name: impl Into<String>, priority: i32, f: F)
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        let index = self
+            .links
+            .iter()
+            .position(|link| link.priority > priority)
+            .unwrap_or(self.links.len());
+        self.links.insert(index, Link::new(name, priority, f));
+    }
+
+    /// Removes the stage with the given name. Returns `false` if there is no
+    /// such stage.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(index) => {
+                self.links.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes every stage with a priority outside of the given range.
+    pub fn retain_priorities(&mut self, range: impl RangeBounds<i32>) {
+        self.links.retain(|link| range.contains(&link.priority));
+    }
+
+    /// Moves the stage with the given name to the given index, shifting the
+    /// stages in between. Returns `false` if there is no such stage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index >= len`.
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        assert!(index < self.links.len(), "index out of bounds");
+        match self.position(name) {
+            Some(from) => {
+                let link = self.links.remove(from);
+                self.links.insert(index, link);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Swaps the stages at the given indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.links.swap(a, b);
+    }
+
+    /// Sorts the stages by priority, keeping stages with equal priorities in
+    /// their current order.
+    pub fn sort_by_priority(&mut self) {
+        self.links.sort_by_key(|link| link.priority);
+    }
+
+    /// Returns the index of the stage with the given name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.links.iter().position(|link| link.name == name)
+    }
+
+    /// Returns `true` if the chain has a stage with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Returns the priority of the stage with the given name.
+    pub fn priority(&self, name: &str) -> Option<i32> {
+        self.position(name).map(|index| self.links[index].priority)
+    }
+
+    /// Returns an iterator over the names of the stages, in the order they
+    /// run.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.links.iter().map(|link| link.name.as_str())
+    }
+
+    /// Returns the number of stages in the chain.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Returns `true` if the chain has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Runs every stage of the chain in order on the given value.
+    pub fn apply(&self, value: T) -> T {
+        self.links.iter().fold(value, |value, link| (link.f)(value))
+    }
+
+    /// Folds the chain into a single closure.
+    pub fn into_fn(self) -> impl Fn(T) -> T {
+        move |value| self.apply(value)
+    }
+}
+
+impl<T> Link<T> {
+    fn new<F>(name: impl Into<String>, priority: i32, f: F) -> Self
+    where
+        F: Fn(T) -> T + 'static,
+    {
+        Self {
+            name: name.into(),
+            priority,
+            f: Box::new(f),
+        }
+    }
+}
+
+impl<T> Default for Chain<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Chain<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.links.iter().map(|link| (&link.name, link.priority)))
+            .finish()
+    }
+}
+
+impl<T> Stage<T> for Chain<T> {
+    type Output = T;
+
+    fn run_once(self, input: T) -> T {
+        self.apply(input)
+    }
+}
+
+impl<T> StageMut<T> for Chain<T> {
+    fn run_mut(&mut self, input: T) -> T {
+        self.apply(input)
+    }
+}
+
+impl<T> StageFn<T> for Chain<T> {
+    fn run(&self, input: T) -> T {
+        self.apply(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn middleware() -> Chain<Vec<&'static str>> {
+        let mut chain = Chain::new();
+        chain.insert_by_priority("auth", 10, |mut v: Vec<_>| {
+            v.push("auth");
+            v
+        });
+        chain.insert_by_priority("log", 0, |mut v: Vec<_>| {
+            v.push("log");
+            v
+        });
+        chain.insert_by_priority("cache", 10, |mut v: Vec<_>| {
+            v.push("cache");
+            v
+        });
+        chain.insert_by_priority("compress", 20, |mut v: Vec<_>| {
+            v.push("compress");
+            v
+        });
+        chain
+    }
+
+    #[test]
+    fn test_chain_priority_order() {
+        let chain = middleware();
+        assert_eq!(chain.len(), 4);
+        assert_eq!(
+            chain.apply(Vec::new()),
+            ["log", "auth", "cache", "compress"]
+        );
+        assert_eq!(chain.priority("cache"), Some(10));
+        assert_eq!(chain.priority("missing"), None);
+        assert_eq!(
+            format!("{chain:?}"),
+            r#"{"log": 0, "auth": 10, "cache": 10, "compress": 20}"#
+        );
+    }
+
+    #[test]
+    fn test_chain_insert_and_remove() {
+        let mut chain = middleware();
+        assert!(chain.insert_before("auth", "cors", |mut v| {
+            v.push("cors");
+            v
+        }));
+        assert!(chain.insert_after("compress", "metrics", |mut v| {
+            v.push("metrics");
+            v
+        }));
+        assert!(!chain.insert_after("missing", "never", |v| v));
+        assert_eq!(chain.priority("cors"), Some(10));
+        assert_eq!(
+            chain.names().collect::<Vec<_>>(),
+            ["log", "cors", "auth", "cache", "compress", "metrics"]
+        );
+
+        assert!(chain.remove("cache"));
+        assert!(!chain.remove("cache"));
+        chain.retain_priorities(..20);
+        assert_eq!(chain.apply(Vec::new()), ["log", "cors", "auth"]);
+    }
+
+    #[test]
+    fn test_chain_reorder() {
+        let mut chain = middleware();
+        assert!(chain.move_to("log", 3));
+        chain.swap(0, 1);
+        assert_eq!(
+            chain.apply(Vec::new()),
+            ["cache", "auth", "compress", "log"]
+        );
+        chain.sort_by_priority();
+        assert_eq!(
+            chain.apply(Vec::new()),
+            ["log", "cache", "auth", "compress"]
+        );
+    }
+
+    #[test]
+    fn test_chain_as_stage() {
+        let chain = Chain::new()
+            .then("double", |n: i32| n * 2)
+            .then("inc", |n| n + 1);
+        let pipeline = Pipeline::new(|s: &str| s.len() as i32)
+            .then(chain.into_fn())
+            .then(|n| n.to_string());
+        assert_eq!(pipeline.call("foo"), "7");
+
+        let empty = Chain::<i32>::default();
+        assert_eq!(Pipeline::new(empty).call(5), 5);
+    }
+}
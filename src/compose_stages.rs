@@ -0,0 +1,74 @@
+//! Support for the [`compose!`](crate::compose) macro.
+//!
+//! Each stage is added by its own call to [`__compose_stage`], which is the
+//! only place that requires the stage to accept the previous output. The call
+//! is then stored as a plain function pointer, so a mismatched stage is
+//! reported once, at the stage itself, and not again by the stages after it.
+
+/// The first stage of a composed pipeline.
+pub struct First<F, A, B> {
+    stage: F,
+    call: fn(F, A) -> B,
+}
+
+/// A composed pipeline followed by one more stage.
+pub struct Then<P, F, B, C> {
+    pipeline: P,
+    stage: F,
+    call: fn(F, B) -> C,
+}
+
+/// A pipeline built by [`compose!`](crate::compose), run with its input.
+pub trait Composable<A> {
+    type Output;
+
+    fn run(self, a: A) -> Self::Output;
+}
+
+impl<F, A, B> Composable<A> for First<F, A, B> {
+    type Output = B;
+
+    fn run(self, a: A) -> B {
+        (self.call)(self.stage, a)
+    }
+}
+
+impl<P, F, A, B, C> Composable<A> for Then<P, F, B, C>
+where
+    P: Composable<A, Output = B>,
+{
+    type Output = C;
+
+    fn run(self, a: A) -> C {
+        (self.call)(self.stage, self.pipeline.run(a))
+    }
+}
+
+pub fn __compose_first<F, A, B>(stage: F) -> First<F, A, B>
+where
+    F: FnOnce(A) -> B,
+{
+    First {
+        stage,
+        call: |f, a| f(a),
+    }
+}
+
+pub fn __compose_stage<P, F, A, B, C>(pipeline: P, stage: F) -> Then<P, F, B, C>
+where
+    P: Composable<A, Output = B>,
+    F: FnOnce(B) -> C,
+{
+    Then {
+        pipeline,
+        stage,
+        call: |f, b| f(b),
+    }
+}
+
+pub fn __compose_finish<P, A>(pipeline: P) -> impl FnOnce(A) -> P::Output
+where
+    P: Composable<A>,
+{
+    move |a| pipeline.run(a)
+}
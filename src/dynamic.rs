@@ -0,0 +1,562 @@
+use crate::{Stage, StageFn};
+use std::any::{type_name, Any, TypeId};
+use std::error::Error;
+use std::fmt;
+
+/// The runtime type information of a stage's input or output.
+#[derive(Clone, Copy)]
+struct TypeInfo {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl TypeInfo {
+    fn of<T: Any>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+}
+
+/// A type-erased value passed between stages.
+type AnyValue = Box<dyn Any>;
+
+/// A function taking and returning type-erased values.
+enum ErasedFn {
+    /// A stage that can be run any number of times.
+    Fn(Box<dyn Fn(AnyValue) -> AnyValue>),
+    /// A stage that can only be run once.
+    Once(Box<dyn FnOnce(AnyValue) -> AnyValue>),
+}
+
+/// A type-erased pipeline stage.
+struct DynStage {
+    input: TypeInfo,
+    output: TypeInfo,
+    run: ErasedFn,
+}
+
+/// A pipeline whose stages are chosen at runtime.
+///
+/// Each stage is a statically typed function, closure or
+/// [`Pipeline`](crate::Pipeline) whose input and output types are erased when
+/// it is added. Stages are checked against each other as they are added, so a
+/// `DynPipeline` always lines up internally, and its input and output types
+/// are checked again when it is run or converted back into a typed function.
+///
+/// Stages that can only be called once, such as pipelines built with
+/// [`pipe`](fn@crate::pipe) and [`Pipe::pipe`](crate::Pipe::pipe), are added with
+/// [`push_once`](DynPipeline::push_once). A pipeline holding such a stage can
+/// only be run by value, with [`run_once`](DynPipeline::run_once) or
+/// [`into_fn_once`](DynPipeline::into_fn_once).
+///
+/// ```
+/// # use pipe::*;
+/// let config = ["trim", "len", "double"];
+///
+/// let mut pipeline = DynPipeline::new();
+/// for stage in config {
+///     match stage {
+///         "trim" => pipeline.push(|s: String| s.trim().to_owned()),
+///         "len" => pipeline.push(|s: String| s.len()),
+///         "double" => pipeline.push(|n: usize| n * 2),
+///         _ => unreachable!(),
+///     }
+///     .unwrap();
+/// }
+///
+/// assert_eq!(pipeline.run::<String, usize>(" foo ".to_owned()), Ok(6));
+/// assert!(pipeline.push(|s: String| s.len()).is_err());
+/// ```
+#[derive(Default)]
+pub struct DynPipeline {
+    stages: Vec<DynStage>,
+}
+
+impl DynPipeline {
+    /// Creates an empty pipeline, which returns its input unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a pipeline from a statically typed function, closure or
+    /// [`Pipeline`](crate::Pipeline).
+    pub fn from_stage<S, A>(stage: S) -> Self
+    where
+        S: StageFn<A> + 'static,
+        A: 'static,
+        S::Output: 'static,
+    {
+        let mut pipeline = Self::new();
+        pipeline.stages.push(DynStage::new(stage));
+        pipeline
+    }
+
+    /// Creates a pipeline from a statically typed stage that can only be
+    /// called once, such as a pipeline built with [`pipe`](fn@crate::pipe).
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let greeting = String::from("hello");
+    /// let pipeline = DynPipeline::from_stage_once(
+    ///     pipe(move |name: &'static str| format!("{greeting}, {name}")).pipe(|s| s.len()),
+    /// );
+    /// assert!(pipeline.run::<&str, usize>("world").is_err());
+    /// assert_eq!(pipeline.run_once::<&str, usize>("world"), Ok(12));
+    /// ```
+    pub fn from_stage_once<S, A>(stage: S) -> Self
+    where
+        S: Stage<A> + 'static,
+        A: 'static,
+        S::Output: 'static,
+    {
+        let mut pipeline = Self::new();
+        pipeline.stages.push(DynStage::new_once(stage));
+        pipeline
+    }
+
+    /// Adds a statically typed function, closure or
+    /// [`Pipeline`](crate::Pipeline) as the last stage of the pipeline.
+    ///
+    /// Returns an error, leaving the pipeline unchanged, if the input type of
+    /// the stage does not match the output type of the current last stage.
+    pub fn push<S, A>(&mut self, stage: S) -> Result<(), DynPipelineError>
+    where
+        S: StageFn<A> + 'static,
+        A: 'static,
+        S::Output: 'static,
+    {
+        self.push_stage(DynStage::new(stage))
+    }
+
+    /// Adds a statically typed stage that can only be called once as the last
+    /// stage of the pipeline. The pipeline can then only be run by value.
+    ///
+    /// Returns an error, leaving the pipeline unchanged, if the input type of
+    /// the stage does not match the output type of the current last stage.
+    pub fn push_once<S, A>(&mut self, stage: S) -> Result<(), DynPipelineError>
+    where
+        S: Stage<A> + 'static,
+        A: 'static,
+        S::Output: 'static,
+    {
+        self.push_stage(DynStage::new_once(stage))
+    }
+
+    fn push_stage(&mut self, stage: DynStage) -> Result<(), DynPipelineError> {
+        if let Some(last) = self.stages.last() {
+            if last.output.id != stage.input.id {
+                return Err(DynPipelineError::StageMismatch {
+                    stage: self.stages.len(),
+                    expected: stage.input.name,
+                    found: last.output.name,
+                });
+            }
+        }
+        self.stages.push(stage);
+        Ok(())
+    }
+
+    /// Adds every stage of another pipeline to the end of this one.
+    ///
+    /// Returns an error, leaving both pipelines unchanged, if the input type of
+    /// the other pipeline does not match the output type of this one.
+    pub fn append(&mut self, other: &mut Self) -> Result<(), DynPipelineError> {
+        if let (Some(last), Some(first)) = (self.stages.last(), other.stages.first()) {
+            if last.output.id != first.input.id {
+                return Err(DynPipelineError::StageMismatch {
+                    stage: self.stages.len(),
+                    expected: first.input.name,
+                    found: last.output.name,
+                });
+            }
+        }
+        self.stages.append(&mut other.stages);
+        Ok(())
+    }
+
+    /// Returns the number of stages in the pipeline.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Returns `true` if the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Returns the name of the pipeline's input type, or `None` if the
+    /// pipeline is empty.
+    pub fn input_type_name(&self) -> Option<&'static str> {
+        self.stages.first().map(|stage| stage.input.name)
+    }
+
+    /// Returns the name of the pipeline's output type, or `None` if the
+    /// pipeline is empty.
+    pub fn output_type_name(&self) -> Option<&'static str> {
+        self.stages.last().map(|stage| stage.output.name)
+    }
+
+    /// Checks that the pipeline takes inputs of type `A` and produces outputs
+    /// of type `B`.
+    fn check_types<A: 'static, B: 'static>(&self) -> Result<(), DynPipelineError> {
+        let (input, output) = match (self.stages.first(), self.stages.last()) {
+            (Some(first), Some(last)) => (first.input, last.output),
+            _ => (TypeInfo::of::<A>(), TypeInfo::of::<A>()),
+        };
+        if input.id != TypeId::of::<A>() {
+            return Err(DynPipelineError::InputMismatch {
+                expected: input.name,
+                found: type_name::<A>(),
+            });
+        }
+        if output.id != TypeId::of::<B>() {
+            return Err(DynPipelineError::OutputMismatch {
+                expected: output.name,
+                found: type_name::<B>(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that every stage of the pipeline can be run more than once.
+    fn check_reusable(&self) -> Result<(), DynPipelineError> {
+        match self
+            .stages
+            .iter()
+            .position(|stage| matches!(stage.run, ErasedFn::Once(_)))
+        {
+            Some(stage) => Err(DynPipelineError::OnceStage { stage }),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs the pipeline with the given input, without checking its types.
+    /// Every stage must be reusable.
+    fn run_unchecked<A: 'static, B: 'static>(stages: &[DynStage], input: A) -> B {
+        let output = stages
+            .iter()
+            .fold(Box::new(input) as AnyValue, |value, stage| {
+                match &stage.run {
+                    ErasedFn::Fn(run) => run(value),
+                    ErasedFn::Once(_) => unreachable!("pipeline stages should have been checked"),
+                }
+            });
+        *output
+            .downcast()
+            .expect("pipeline output type should have been checked")
+    }
+
+    /// Runs the pipeline with the given input, consuming its stages, without
+    /// checking its types.
+    fn run_once_unchecked<A: 'static, B: 'static>(stages: Vec<DynStage>, input: A) -> B {
+        let output =
+            stages
+                .into_iter()
+                .fold(Box::new(input) as AnyValue, |value, stage| {
+                    match stage.run {
+                        ErasedFn::Fn(run) => run(value),
+                        ErasedFn::Once(run) => run(value),
+                    }
+                });
+        *output
+            .downcast()
+            .expect("pipeline output type should have been checked")
+    }
+
+    /// Runs the pipeline with the given input.
+    ///
+    /// Returns an error if the pipeline does not take inputs of type `A` or
+    /// does not produce outputs of type `B`, or if it has a stage that can
+    /// only be run once. No stage is run if an error is returned.
+    pub fn run<A: 'static, B: 'static>(&self, input: A) -> Result<B, DynPipelineError> {
+        self.check_types::<A, B>()?;
+        self.check_reusable()?;
+        Ok(Self::run_unchecked(&self.stages, input))
+    }
+
+    /// Runs the pipeline with the given input, consuming it. Unlike
+    /// [`run`](DynPipeline::run), this also runs stages that can only be
+    /// called once.
+    ///
+    /// Returns an error if the pipeline does not take inputs of type `A` or
+    /// does not produce outputs of type `B`.
+    pub fn run_once<A: 'static, B: 'static>(self, input: A) -> Result<B, DynPipelineError> {
+        self.check_types::<A, B>()?;
+        Ok(Self::run_once_unchecked(self.stages, input))
+    }
+
+    /// Converts the pipeline into a statically typed closure.
+    ///
+    /// Returns an error if the pipeline does not take inputs of type `A` or
+    /// does not produce outputs of type `B`, or if it has a stage that can
+    /// only be run once. The pipeline is only checked once, so the returned
+    /// closure cannot fail.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let mut pipeline = DynPipeline::from_stage(pipe_fn(|n: i32| n + 1).pipe_fn(|n| n * 2));
+    /// pipeline.push(|n: i32| n.to_string()).unwrap();
+    ///
+    /// let typed = pipeline.into_fn::<i32, String>().unwrap();
+    /// assert_eq!(typed(1), "4");
+    /// ```
+    pub fn into_fn<A: 'static, B: 'static>(self) -> Result<impl Fn(A) -> B, DynPipelineError> {
+        self.check_types::<A, B>()?;
+        self.check_reusable()?;
+        Ok(move |input| Self::run_unchecked(&self.stages, input))
+    }
+
+    /// Converts the pipeline into a statically typed closure that can be
+    /// called once, including stages that can only be called once.
+    ///
+    /// Returns an error if the pipeline does not take inputs of type `A` or
+    /// does not produce outputs of type `B`.
+    pub fn into_fn_once<A: 'static, B: 'static>(
+        self,
+    ) -> Result<impl FnOnce(A) -> B, DynPipelineError> {
+        self.check_types::<A, B>()?;
+        Ok(move |input| Self::run_once_unchecked(self.stages, input))
+    }
+}
+
+impl DynStage {
+    fn new<S, A>(stage: S) -> Self
+    where
+        S: StageFn<A> + 'static,
+        A: 'static,
+        S::Output: 'static,
+    {
+        Self {
+            input: TypeInfo::of::<A>(),
+            output: TypeInfo::of::<S::Output>(),
+            run: ErasedFn::Fn(Box::new(move |input| {
+                let input = input
+                    .downcast()
+                    .expect("stage input type should have been checked");
+                Box::new(stage.run(*input))
+            })),
+        }
+    }
+
+    fn new_once<S, A>(stage: S) -> Self
+    where
+        S: Stage<A> + 'static,
+        A: 'static,
+        S::Output: 'static,
+    {
+        Self {
+            input: TypeInfo::of::<A>(),
+            output: TypeInfo::of::<S::Output>(),
+            run: ErasedFn::Once(Box::new(move |input| {
+                let input = input
+                    .downcast()
+                    .expect("stage input type should have been checked");
+                Box::new(stage.run_once(*input))
+            })),
+        }
+    }
+}
+
+impl fmt::Debug for DynPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.stages
+                    .iter()
+                    .map(|stage| format!("{} -> {}", stage.input.name, stage.output.name)),
+            )
+            .finish()
+    }
+}
+
+/// An error produced when the types of a [`DynPipeline`] do not line up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DynPipelineError {
+    /// A stage's input type does not match the output type of the stage
+    /// before it.
+    StageMismatch {
+        /// The index of the stage, starting from `0`.
+        stage: usize,
+        /// The input type of the stage.
+        expected: &'static str,
+        /// The output type of the stage before it.
+        found: &'static str,
+    },
+    /// The pipeline was given an input of the wrong type.
+    InputMismatch {
+        /// The input type of the pipeline.
+        expected: &'static str,
+        /// The type of the given input.
+        found: &'static str,
+    },
+    /// The pipeline was asked for an output of the wrong type.
+    OutputMismatch {
+        /// The output type of the pipeline.
+        expected: &'static str,
+        /// The requested output type.
+        found: &'static str,
+    },
+    /// The pipeline was run by reference, but has a stage that can only be
+    /// run once.
+    OnceStage {
+        /// The index of the stage, starting from `0`.
+        stage: usize,
+    },
+}
+
+impl fmt::Display for DynPipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StageMismatch {
+                stage,
+                expected,
+                found,
+            } => write!(
+                f,
+                "pipeline stage {stage} expects an input of type `{expected}`, but the previous stage produces `{found}`"
+            ),
+            Self::InputMismatch { expected, found } => write!(
+                f,
+                "pipeline expects an input of type `{expected}`, but was given `{found}`"
+            ),
+            Self::OutputMismatch { expected, found } => write!(
+                f,
+                "pipeline produces an output of type `{expected}`, but `{found}` was requested"
+            ),
+            Self::OnceStage { stage } => write!(
+                f,
+                "pipeline stage {stage} can only be run once, with `run_once` or `into_fn_once`"
+            ),
+        }
+    }
+}
+
+impl Error for DynPipelineError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::any::type_name;
+
+    fn build(config: &[&str]) -> Result<DynPipeline, DynPipelineError> {
+        let mut pipeline = DynPipeline::new();
+        for stage in config {
+            match *stage {
+                "parse" => pipeline.push(|s: String| s.parse::<i64>().unwrap_or_default())?,
+                "negate" => pipeline.push(|n: i64| -n)?,
+                "format" => pipeline.push(|n: i64| format!("<{n}>"))?,
+                "upper" => pipeline.push(|s: String| s.to_uppercase())?,
+                _ => panic!("unknown stage {stage}"),
+            }
+        }
+        Ok(pipeline)
+    }
+
+    #[test]
+    fn test_dyn_pipeline_run() {
+        let pipeline = build(&["parse", "negate", "negate", "format"]).unwrap();
+        assert_eq!(pipeline.len(), 4);
+        assert_eq!(pipeline.input_type_name(), Some(type_name::<String>()));
+        assert_eq!(pipeline.output_type_name(), Some(type_name::<String>()));
+        assert_eq!(
+            pipeline.run::<_, String>("42".to_owned()),
+            Ok("<42>".to_owned())
+        );
+        assert_eq!(
+            pipeline.run::<_, String>("7".to_owned()),
+            Ok("<7>".to_owned())
+        );
+
+        let empty = DynPipeline::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.run::<i32, i32>(1), Ok(1));
+    }
+
+    #[test]
+    fn test_dyn_pipeline_stage_mismatch() {
+        let err = build(&["parse", "negate", "upper"]).unwrap_err();
+        assert_eq!(
+            err,
+            DynPipelineError::StageMismatch {
+                stage: 2,
+                expected: type_name::<String>(),
+                found: "i64",
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "pipeline stage 2 expects an input of type `{}`, but the previous stage produces `i64`",
+                type_name::<String>()
+            )
+        );
+
+        let mut numbers = build(&["parse"]).unwrap();
+        let mut strings = build(&["upper"]).unwrap();
+        assert!(numbers.append(&mut strings).is_err());
+        assert_eq!((numbers.len(), strings.len()), (1, 1));
+        let mut negate = build(&["negate", "format"]).unwrap();
+        numbers.append(&mut negate).unwrap();
+        assert_eq!((numbers.len(), negate.len()), (3, 0));
+    }
+
+    #[test]
+    fn test_dyn_pipeline_io_mismatch() {
+        let pipeline = build(&["parse", "negate"]).unwrap();
+        assert_eq!(
+            pipeline.run::<&str, i64>("1"),
+            Err(DynPipelineError::InputMismatch {
+                expected: type_name::<String>(),
+                found: "&str",
+            })
+        );
+        assert_eq!(
+            pipeline.run::<String, i32>("1".to_owned()),
+            Err(DynPipelineError::OutputMismatch {
+                expected: "i64",
+                found: "i32",
+            })
+        );
+        assert!(pipeline.into_fn::<String, String>().is_err());
+    }
+
+    #[test]
+    fn test_dyn_pipeline_static_conversion() {
+        let pipeline = DynPipeline::from_stage(Pipeline::new(|n: i64| n + 1).then(|n| n * 2));
+        let typed = pipeline.into_fn::<i64, i64>().unwrap();
+        let again = pipe_fn(typed).pipe_fn(|n| n - 1);
+        assert_eq!(again(1), 3);
+        assert_eq!(
+            format!("{:?}", build(&["parse", "format"]).unwrap()),
+            format!(
+                "[\"{string} -> i64\", \"i64 -> {string}\"]",
+                string = type_name::<String>()
+            )
+        );
+    }
+
+    #[test]
+    fn test_dyn_pipeline_once_stages() {
+        let suffix = String::from("!");
+        let mut pipeline = build(&["parse", "negate"]).unwrap();
+        pipeline
+            .push_once(pipe(|n: i64| n.to_string()).pipe(move |s| s + &suffix))
+            .unwrap();
+        pipeline.push(|s: String| s.len()).unwrap();
+        assert_eq!(
+            pipeline.run::<String, usize>("12".to_owned()),
+            Err(DynPipelineError::OnceStage { stage: 2 })
+        );
+        assert!(pipeline.push_once(|n: i64| n).is_err());
+        assert_eq!(pipeline.run_once::<String, usize>("12".to_owned()), Ok(4));
+
+        let pipeline = DynPipeline::from_stage_once(pipe(|s: String| s.len()));
+        assert!(pipeline.into_fn_once::<String, String>().is_err());
+        let typed = DynPipeline::from_stage_once(pipe(|s: String| s.len()))
+            .into_fn_once::<String, usize>()
+            .unwrap();
+        assert_eq!(typed("foo".to_owned()), 3);
+    }
+}
@@ -0,0 +1,147 @@
+use std::iter::{Filter, FlatMap, Map, Take};
+
+/// Creates a pipeline stage that maps every item of an iterable input with
+/// the provided function or closure, returning a lazy iterator.
+///
+/// The input may be any type implementing [`IntoIterator`]. The function is
+/// cloned each time the stage is run, so the stage implements `Fn` even if
+/// the function only implements `FnMut`. As with the other stage functions,
+/// the item type usually needs to be annotated on the first closure of a
+/// pipeline.
+///
+/// ```
+/// # use pipe::*;
+/// let squares = pipe(|n: u32| 1..=n)
+///     .pipe(map_each(|n: u32| n * n))
+///     .pipe(collect_into::<Vec<_>, _>());
+/// assert_eq!(squares(4), [1, 4, 9, 16]);
+/// ```
+pub fn map_each<I, F, B>(f: F) -> impl Fn(I) -> Map<I::IntoIter, F>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> B + Clone,
+{
+    move |iter| iter.into_iter().map(f.clone())
+}
+
+/// Creates a pipeline stage that keeps only the items of an iterable input
+/// for which the predicate holds, returning a lazy iterator.
+///
+/// ```
+/// # use pipe::*;
+/// let remove_long_words = pipe(|s: &str| s.split(' '))
+///     .pipe(filter_each(|s: &&str| s.len() <= 4))
+///     .pipe(collect_into::<Vec<_>, _>())
+///     .pipe(|words| words.join(" "));
+/// assert_eq!(remove_long_words("foo bar hello world baz"), "foo bar baz");
+/// ```
+pub fn filter_each<I, P>(pred: P) -> impl Fn(I) -> Filter<I::IntoIter, P>
+where
+    I: IntoIterator,
+    P: FnMut(&I::Item) -> bool + Clone,
+{
+    move |iter| iter.into_iter().filter(pred.clone())
+}
+
+/// Creates a pipeline stage that maps every item of an iterable input to an
+/// iterable value with the provided function or closure, and flattens the
+/// results into a single lazy iterator.
+///
+/// ```
+/// # use pipe::*;
+/// let chars = pipe(|lines: Vec<&str>| lines)
+///     .pipe(flat_map_each(|line: &str| line.chars()))
+///     .pipe(collect_into::<String, _>());
+/// assert_eq!(chars(vec!["ab", "cd"]), "abcd");
+/// ```
+pub fn flat_map_each<I, F, U>(f: F) -> impl Fn(I) -> FlatMap<I::IntoIter, U, F>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> U + Clone,
+    U: IntoIterator,
+{
+    move |iter| iter.into_iter().flat_map(f.clone())
+}
+
+/// Creates a pipeline stage that keeps at most the first `n` items of an
+/// iterable input, returning a lazy iterator.
+///
+/// ```
+/// # use pipe::*;
+/// let first_words = pipe(|s: &str| s.split_whitespace())
+///     .pipe(take(2))
+///     .pipe(collect_into::<Vec<_>, _>());
+/// assert_eq!(first_words("one two three"), ["one", "two"]);
+/// ```
+pub fn take<I>(n: usize) -> impl Fn(I) -> Take<I::IntoIter>
+where
+    I: IntoIterator,
+{
+    move |iter| iter.into_iter().take(n)
+}
+
+/// Creates a pipeline stage that collects the items of an iterable input
+/// into a collection of type `C`, such as a [`Vec`] or a [`String`].
+///
+/// The input type is the second type parameter, so when the collection type
+/// is given explicitly, the input type is left to inference with `_`:
+///
+/// ```
+/// # use pipe::*;
+/// let letters = pipe(|s: &str| s.chars())
+///     .pipe(filter_each(|c: &char| c.is_alphabetic()))
+///     .pipe(collect_into::<String, _>());
+/// assert_eq!(letters("a1b2"), "ab");
+/// ```
+pub fn collect_into<C, I>() -> impl Fn(I) -> C
+where
+    I: IntoIterator,
+    C: FromIterator<I::Item>,
+{
+    |iter| iter.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn test_each_stages_any_iterable() {
+        let even_doubles = Pipeline::new(filter_each(|n: &i32| n % 2 == 0))
+            .then(map_each(|n| n * 2))
+            .then(take(3))
+            .then(collect_into::<Vec<_>, _>());
+        assert_eq!(even_doubles.call(vec![1, 2, 3, 4, 6, 8]), [4, 8, 12]);
+        assert_eq!(even_doubles.call(vec![]), []);
+
+        let unique_lengths = pipe(|words: [&str; 4]| words)
+            .pipe(map_each(str::len))
+            .pipe(collect_into::<BTreeSet<_>, _>());
+        assert_eq!(
+            unique_lengths(["a", "bb", "cc", "d"]),
+            BTreeSet::from([1, 2])
+        );
+    }
+
+    #[test]
+    fn test_each_stages_stateful() {
+        let mut next = 0;
+        let numbered = pipe_fn(|s: &str| s.lines())
+            .pipe_fn(map_each(move |line: &str| {
+                next += 1;
+                (line, next)
+            }))
+            .pipe_fn(collect_into::<HashMap<_, _>, _>());
+        assert_eq!(numbered("a\nb"), HashMap::from([("a", 1), ("b", 2)]));
+        assert_eq!(numbered("c"), HashMap::from([("c", 1)]));
+    }
+
+    #[test]
+    fn test_flat_map_each() {
+        let ranges = pipe(|bounds: Vec<(u8, u8)>| bounds)
+            .pipe(flat_map_each(|(lo, hi): (u8, u8)| lo..hi))
+            .pipe(collect_into::<Vec<_>, _>());
+        assert_eq!(ranges(vec![(0, 2), (5, 7)]), [0, 1, 5, 6]);
+    }
+}
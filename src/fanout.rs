@@ -0,0 +1,218 @@
+/// A tuple of functions that can each be called with a clone of the same
+/// input. See the [`fanout`] function.
+///
+/// This trait is implemented for tuples of up to 12 functions or closures
+/// implementing `Fn(B)`, where `B: Clone`.
+pub trait Fanout<B> {
+    /// The tuple of the functions' outputs.
+    type Output;
+
+    /// Calls every function with the input, cloning it for all but the last.
+    fn fan_out(&self, input: B) -> Self::Output;
+}
+
+/// A tuple of functions that can each be called with a reference to the same
+/// input. See the [`fanout_ref`] function.
+///
+/// This trait is implemented for tuples of up to 12 functions or closures
+/// implementing `Fn(&B)`.
+pub trait FanoutRef<B> {
+    /// The tuple of the functions' outputs.
+    type Output;
+
+    /// Calls every function with a reference to the input.
+    fn fan_out_ref(&self, input: &B) -> Self::Output;
+}
+
+/// A function that can be called with the elements of a tuple as separate
+/// arguments. See the [`join_with`] function.
+///
+/// This trait is implemented for functions or closures implementing
+/// `Fn(A1, A2, ...)` taking up to 12 arguments, for the tuple
+/// `(A1, A2, ...)`.
+pub trait JoinWith<T> {
+    /// The output of the function.
+    type Output;
+
+    /// Calls the function with the elements of the tuple.
+    fn join(&self, args: T) -> Self::Output;
+}
+
+macro_rules! impl_fanout {
+    ( $( $f:ident $c:ident $idx:tt ),* ; $last_f:ident $last_c:ident $last_idx:tt ) => {
+        impl<B, $( $f, $c, )* $last_f, $last_c> Fanout<B> for ( $( $f, )* $last_f, )
+        where
+            B: Clone,
+            $( $f: Fn(B) -> $c, )*
+            $last_f: Fn(B) -> $last_c,
+        {
+            type Output = ( $( $c, )* $last_c, );
+
+            fn fan_out(&self, input: B) -> Self::Output {
+                ( $( (self.$idx)(input.clone()), )* (self.$last_idx)(input), )
+            }
+        }
+
+        impl<B, $( $f, $c, )* $last_f, $last_c> FanoutRef<B> for ( $( $f, )* $last_f, )
+        where
+            $( $f: Fn(&B) -> $c, )*
+            $last_f: Fn(&B) -> $last_c,
+        {
+            type Output = ( $( $c, )* $last_c, );
+
+            fn fan_out_ref(&self, input: &B) -> Self::Output {
+                ( $( (self.$idx)(input), )* (self.$last_idx)(input), )
+            }
+        }
+
+        impl<J, R, $( $c, )* $last_c> JoinWith<( $( $c, )* $last_c, )> for J
+        where
+            J: Fn( $( $c, )* $last_c ) -> R,
+        {
+            type Output = R;
+
+            #[allow(non_snake_case)]
+            fn join(&self, args: ( $( $c, )* $last_c, )) -> R {
+                self( $( args.$idx, )* args.$last_idx )
+            }
+        }
+    };
+}
+
+impl_fanout!(; F1 C1 0);
+impl_fanout!(F1 C1 0; F2 C2 1);
+impl_fanout!(F1 C1 0, F2 C2 1; F3 C3 2);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2; F4 C4 3);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3; F5 C5 4);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4; F6 C6 5);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4, F6 C6 5; F7 C7 6);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4, F6 C6 5, F7 C7 6; F8 C8 7);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4, F6 C6 5, F7 C7 6, F8 C8 7; F9 C9 8);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4, F6 C6 5, F7 C7 6, F8 C8 7, F9 C9 8; F10 C10 9);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4, F6 C6 5, F7 C7 6, F8 C8 7, F9 C9 8, F10 C10 9; F11 C11 10);
+impl_fanout!(F1 C1 0, F2 C2 1, F3 C3 2, F4 C4 3, F5 C5 4, F6 C6 5, F7 C7 6, F8 C8 7, F9 C9 8, F10 C10 9, F11 C11 10; F12 C12 11);
+
+/// Creates a pipeline stage that feeds its input into each function of a
+/// tuple, producing a tuple of their outputs. The input is cloned for every
+/// function but the last.
+///
+/// As the functions are given in a tuple, the input types of closures usually
+/// need to be annotated.
+///
+/// ```
+/// # use pipe::*;
+/// let stats = pipe(|s: &str| s.split_whitespace().collect::<Vec<_>>()).pipe(fanout((
+///     |words: Vec<&str>| words.len(),
+///     |words: Vec<&str>| words.iter().map(|w| w.len()).max(),
+///     |words: Vec<&str>| words.join("-"),
+/// )));
+/// assert_eq!(stats("foo hello bar"), (3, Some(5), "foo-hello-bar".to_owned()));
+/// ```
+pub fn fanout<B, T>(fs: T) -> impl Fn(B) -> T::Output
+where
+    T: Fanout<B>,
+{
+    move |b| fs.fan_out(b)
+}
+
+/// Creates a pipeline stage that passes a reference to its input into each
+/// function of a tuple, producing a tuple of their outputs. Unlike [`fanout`],
+/// the input does not need to implement `Clone`.
+///
+/// ```
+/// # use pipe::*;
+/// let bounds = pipe(|s: &str| s.chars().collect::<Vec<_>>()).pipe(fanout_ref((
+///     |chars: &Vec<char>| chars.iter().min().copied(),
+///     |chars: &Vec<char>| chars.iter().max().copied(),
+/// )));
+/// assert_eq!(bounds("pipe"), (Some('e'), Some('p')));
+/// ```
+pub fn fanout_ref<B, T>(fs: T) -> impl Fn(B) -> T::Output
+where
+    T: FanoutRef<B>,
+{
+    move |b| fs.fan_out_ref(&b)
+}
+
+/// Creates a pipeline stage that calls the provided function with the
+/// elements of its tuple input as separate arguments, recombining the results
+/// of a [`fanout`].
+///
+/// The arguments of a closure cannot be inferred from the tuple, so for
+/// closures, a [`Pipe::pipe`](crate::Pipe::pipe) stage destructuring the tuple
+/// is usually more convenient.
+///
+/// ```
+/// # use pipe::*;
+/// fn average(sum: i32, count: usize) -> f64 {
+///     sum as f64 / count as f64
+/// }
+///
+/// let mean = pipe(|v: Vec<i32>| v)
+///     .pipe(fanout((|v: Vec<i32>| v.iter().sum::<i32>(), |v: Vec<i32>| v.len())))
+///     .pipe(join_with(average));
+/// assert_eq!(mean(vec![1, 2, 3, 4]), 2.5);
+/// ```
+pub fn join_with<T, J>(j: J) -> impl Fn(T) -> J::Output
+where
+    J: JoinWith<T>,
+{
+    move |args| j.join(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_fanout_clones_all_but_last() {
+        let input = Rc::new(5);
+        let counts = pipe(|n: Rc<i32>| n).pipe(fanout((
+            |n: Rc<i32>| Rc::strong_count(&n),
+            |n: Rc<i32>| Rc::strong_count(&n),
+            |n: Rc<i32>| *n * 2,
+        )));
+        assert_eq!(counts(input.clone()), (3, 3, 10));
+        assert_eq!(Rc::strong_count(&input), 1);
+    }
+
+    #[test]
+    fn test_fanout_ref_and_join() {
+        fn describe(len: usize, upper: String, first: Option<char>) -> String {
+            format!("{len} {upper} {first:?}")
+        }
+
+        let pipeline = Pipeline::new(|s: &str| s.to_owned())
+            .then(fanout_ref((
+                |s: &String| s.len(),
+                |s: &String| s.to_uppercase(),
+                |s: &String| s.chars().next(),
+            )))
+            .then(join_with(describe));
+        assert_eq!(pipeline.call("foo"), "3 FOO Some('f')");
+        assert_eq!(pipeline.call(""), "0  None");
+    }
+
+    #[test]
+    fn test_fanout_max_arity() {
+        let spread = pipe(|n: u8| n).pipe(fanout((
+            |n: u8| n,
+            |n: u8| n + 1,
+            |n: u8| n + 2,
+            |n: u8| n + 3,
+            |n: u8| n + 4,
+            |n: u8| n + 5,
+            |n: u8| n + 6,
+            |n: u8| n + 7,
+            |n: u8| n + 8,
+            |n: u8| n + 9,
+            |n: u8| n + 10,
+            |n: u8| n + 11,
+        )));
+        assert_eq!(spread(0), (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11));
+
+        let single = pipe(|n: u8| n).pipe(fanout((|n: u8| n * 2,)));
+        assert_eq!(single(2), (4,));
+    }
+}
@@ -1,9 +1,69 @@
+mod arrow;
+mod async_pipe;
+mod branch;
+mod chain;
+mod compose_stages;
+mod dynamic;
+mod each;
+mod fanout;
+mod mut_pipeline;
+mod ops;
+mod option_pipe;
+mod pipe_each;
+mod pipe_value;
+mod pipeline;
+mod ref_pipeline;
+mod tap;
+#[cfg(test)]
+mod test_util;
+mod transduce;
+mod try_pipe;
+
+pub use arrow::{assoc, both, first, second, swap, unassoc, TupleFirst, TuplePair};
+#[doc(hidden)]
+pub use async_pipe::__block_on;
+pub use async_pipe::{pipe_async, AsyncPipe, AsyncThen, SyncThen};
+pub use branch::{branch, pipe_if, split, Either};
+pub use chain::Chain;
+#[doc(hidden)]
+pub use compose_stages::{__compose_finish, __compose_first, __compose_stage};
+pub use dynamic::{DynPipeline, DynPipelineError};
+pub use each::{collect_into, filter_each, flat_map_each, map_each, take};
+pub use fanout::{fanout, fanout_ref, join_with, Fanout, FanoutRef, JoinWith};
+pub use mut_pipeline::MutPipeline;
+pub use ops::P;
+pub use option_pipe::{NoneAt, OptionPipe, TracedOptionPipe};
+pub use pipe_each::{ErrorPolicy, PipeEach, Piped, TryPiped};
+pub use pipe_value::PipeValue;
+pub use pipeline::{start, Composed, Identity, Pipeline, Stage, StageFn, StageMut};
+pub use ref_pipeline::{RefPipeline, RefStage};
+#[doc(hidden)]
+pub use tap::__TAPS_ENABLED;
+pub use tap::{inspect_dbg, tap, tap_mut};
+pub use transduce::{
+    filtering, mapping, partitioning, taking, transduce, transduce_async, Filtering, Mapping,
+    Partitioning, Reducer, Step, Taking, Transducer,
+};
+pub use try_pipe::TryPipe;
+
 /// The `Pipe` trait creates a functional pipe by wrapping operations within one
 /// another. Each call to the `pipe` method creates another wrapper and returns
 /// the resulting function.
 ///
 /// When in scope, this trait is implemented for all types implementing
 /// `FnOnce(A) -> B`, for any types `A` and `B`.
+///
+/// Using a value that is not a pipeline as one is reported with a message
+/// naming the expected input and output. A stage whose argument does not
+/// match the previous output is reported by the compiler's own type mismatch
+/// error (E0631) instead, pointing at the `pipe` call that adds it, since
+/// stable Rust does not apply custom diagnostics to function signature
+/// mismatches.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a pipeline taking `{A}` and producing `{B}`",
+    label = "expected a function or closure implementing `FnOnce({A}) -> {B}`",
+    note = "pipelines are started with `pipe` and extended with `Pipe::pipe`, each stage taking the previous stage's output"
+)]
 pub trait Pipe<A, B, C> {
     /// Wraps the provided function or closure inside the currently constructed
     /// pipeline. See the documentation for the [`pipe`] function for examples.
@@ -24,11 +84,202 @@ where
     }
 }
 
+/// The `PipeMut` trait is the `FnMut` counterpart to [`Pipe`]. Pipelines built
+/// with it can be called any number of times, as long as every stage can be.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnMut(A) -> B`, for any types `A` and `B`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a reusable pipeline taking `{A}` and producing `{B}`",
+    label = "expected a function or closure implementing `FnMut({A}) -> {B}`",
+    note = "every stage of a `pipe_mut` pipeline must implement `FnMut`"
+)]
+pub trait PipeMut<A, B, C> {
+    /// Wraps the provided function or closure inside the currently constructed
+    /// pipeline. See the documentation for the [`pipe_mut`] function for
+    /// examples.
+    fn pipe_mut<F>(self, f: F) -> impl FnMut(A) -> C
+    where
+        F: FnMut(B) -> C;
+}
+
+impl<F1, A, B, C> PipeMut<A, B, C> for F1
+where
+    F1: FnMut(A) -> B,
+{
+    fn pipe_mut<F2>(mut self, mut f: F2) -> impl FnMut(A) -> C
+    where
+        F2: FnMut(B) -> C,
+    {
+        move |a| f(self(a))
+    }
+}
+
+/// The `PipeFn` trait is the `Fn` counterpart to [`Pipe`]. Pipelines built
+/// with it can be called any number of times and shared by reference, as long
+/// as every stage can be.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `Fn(A) -> B`, for any types `A` and `B`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a shareable pipeline taking `{A}` and producing `{B}`",
+    label = "expected a function or closure implementing `Fn({A}) -> {B}`",
+    note = "every stage of a `pipe_fn` pipeline must implement `Fn`"
+)]
+pub trait PipeFn<A, B, C> {
+    /// Wraps the provided function or closure inside the currently constructed
+    /// pipeline. See the documentation for the [`pipe_fn`] function for
+    /// examples.
+    fn pipe_fn<F>(self, f: F) -> impl Fn(A) -> C
+    where
+        F: Fn(B) -> C;
+}
+
+impl<F1, A, B, C> PipeFn<A, B, C> for F1
+where
+    F1: Fn(A) -> B,
+{
+    fn pipe_fn<F2>(self, f: F2) -> impl Fn(A) -> C
+    where
+        F2: Fn(B) -> C,
+    {
+        move |a| f(self(a))
+    }
+}
+
+/// The `Pipe2` trait is the counterpart to [`Pipe`] for pipelines whose first
+/// stage takes two arguments. See the documentation for the [`pipe2`] function
+/// for examples.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnOnce(A1, A2) -> B`, for any types `A1`, `A2` and `B`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a pipeline taking `{A1}` and `{A2}` and producing `{B}`",
+    label = "expected a function or closure implementing `FnOnce({A1}, {A2}) -> {B}`"
+)]
+pub trait Pipe2<A1, A2, B, C> {
+    /// Wraps the provided function or closure inside the currently constructed
+    /// pipeline, which keeps taking two arguments.
+    fn pipe<F>(self, f: F) -> impl FnOnce(A1, A2) -> C
+    where
+        F: FnOnce(B) -> C;
+}
+
+impl<F1, A1, A2, B, C> Pipe2<A1, A2, B, C> for F1
+where
+    F1: FnOnce(A1, A2) -> B,
+{
+    fn pipe<F2>(self, f: F2) -> impl FnOnce(A1, A2) -> C
+    where
+        F2: FnOnce(B) -> C,
+    {
+        |a1, a2| f(self(a1, a2))
+    }
+}
+
+/// The `Pipe3` trait is the counterpart to [`Pipe`] for pipelines whose first
+/// stage takes three arguments. See the documentation for the [`pipe3`]
+/// function for examples.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnOnce(A1, A2, A3) -> B`, for any types `A1`, `A2`, `A3` and `B`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a pipeline taking `{A1}`, `{A2}` and `{A3}` and producing `{B}`",
+    label = "expected a function or closure implementing `FnOnce({A1}, {A2}, {A3}) -> {B}`"
+)]
+pub trait Pipe3<A1, A2, A3, B, C> {
+    /// Wraps the provided function or closure inside the currently constructed
+    /// pipeline, which keeps taking three arguments.
+    fn pipe<F>(self, f: F) -> impl FnOnce(A1, A2, A3) -> C
+    where
+        F: FnOnce(B) -> C;
+}
+
+impl<F1, A1, A2, A3, B, C> Pipe3<A1, A2, A3, B, C> for F1
+where
+    F1: FnOnce(A1, A2, A3) -> B,
+{
+    fn pipe<F2>(self, f: F2) -> impl FnOnce(A1, A2, A3) -> C
+    where
+        F2: FnOnce(B) -> C,
+    {
+        |a1, a2, a3| f(self(a1, a2, a3))
+    }
+}
+
+/// The `PrePipe` trait adapts the input of a pipeline, by wrapping it inside
+/// a stage that runs before it. Where [`Pipe`] appends stages to the end of a
+/// pipeline, `PrePipe` prepends them to the start.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnOnce(A) -> B`, for any types `A` and `B`.
+pub trait PrePipe<A, B> {
+    /// Wraps the currently constructed pipeline inside the provided function
+    /// or closure, which runs first and produces the pipeline's input.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let double = pipe(|n: i32| n * 2);
+    /// let parse_and_double = double.pre_pipe(|s: &str| s.parse().unwrap());
+    /// assert_eq!(parse_and_double("21"), 42);
+    /// ```
+    fn pre_pipe<F, Z>(self, f: F) -> impl FnOnce(Z) -> B
+    where
+        F: FnOnce(Z) -> A;
+
+    /// An alias for [`pre_pipe`](PrePipe::pre_pipe), named after the
+    /// equivalent operation in functional programming.
+    fn contramap<F, Z>(self, f: F) -> impl FnOnce(Z) -> B
+    where
+        F: FnOnce(Z) -> A;
+
+    /// Adapts both the input and the output of the currently constructed
+    /// pipeline at once, running `pre` before it and `post` after it.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let double = pipe(|n: i32| n * 2);
+    /// let double_str = double.dimap(|s: &str| s.parse().unwrap(), |n| n.to_string());
+    /// assert_eq!(double_str("21"), "42");
+    /// ```
+    fn dimap<F, G, Z, C>(self, pre: F, post: G) -> impl FnOnce(Z) -> C
+    where
+        F: FnOnce(Z) -> A,
+        G: FnOnce(B) -> C;
+}
+
+impl<F1, A, B> PrePipe<A, B> for F1
+where
+    F1: FnOnce(A) -> B,
+{
+    fn pre_pipe<F2, Z>(self, f: F2) -> impl FnOnce(Z) -> B
+    where
+        F2: FnOnce(Z) -> A,
+    {
+        |z| self(f(z))
+    }
+
+    fn contramap<F2, Z>(self, f: F2) -> impl FnOnce(Z) -> B
+    where
+        F2: FnOnce(Z) -> A,
+    {
+        self.pre_pipe(f)
+    }
+
+    fn dimap<F2, G, Z, C>(self, pre: F2, post: G) -> impl FnOnce(Z) -> C
+    where
+        F2: FnOnce(Z) -> A,
+        G: FnOnce(B) -> C,
+    {
+        |z| post(self(pre(z)))
+    }
+}
+
 /// This is a convenience function to start a pipeline.
 ///
 /// The compiler often has difficulty inferring pipe input types, so it is
 /// usually a good idea to explicitly provide the input type when using this
-/// function.
+/// function, or to declare it up front with [`start`].
 ///
 /// ```
 /// # use pipe::*;
@@ -46,11 +297,98 @@ where
     f
 }
 
+/// This is a convenience function to start a pipeline that can be called more
+/// than once. Every stage must implement `FnMut`.
+///
+/// ```
+/// # use pipe::*;
+/// let mut seen = Vec::new();
+/// let mut record = pipe_mut(|n: i32| n * 2).pipe_mut(|n| seen.push(n));
+/// record(1);
+/// record(2);
+/// drop(record);
+/// assert_eq!(seen, [2, 4]);
+/// ```
+pub fn pipe_mut<F, A, B>(f: F) -> impl FnMut(A) -> B
+where
+    F: FnMut(A) -> B,
+{
+    f
+}
+
+/// This is a convenience function to start a pipeline that can be called more
+/// than once and shared by reference. Every stage must implement `Fn`.
+///
+/// ```
+/// # use pipe::*;
+/// struct Normalizer<F> {
+///     normalize: F,
+/// }
+///
+/// let normalizer = Normalizer {
+///     normalize: pipe_fn(|s: &str| s.trim()).pipe_fn(|s| s.to_lowercase()),
+/// };
+/// assert_eq!((normalizer.normalize)("  Foo "), "foo");
+/// assert_eq!((normalizer.normalize)("BAR"), "bar");
+/// ```
+pub fn pipe_fn<F, A, B>(f: F) -> impl Fn(A) -> B
+where
+    F: Fn(A) -> B,
+{
+    f
+}
+
+/// This is a convenience function to start a pipeline whose first stage takes
+/// two arguments. The resulting pipeline takes the same two arguments, so it
+/// can replace a two-parameter function directly.
+///
+/// ```
+/// # use pipe::*;
+/// let repeat_upper = pipe2(|s: &str, n: usize| s.repeat(n))
+///     .pipe(|s| s.to_uppercase())
+///     .pipe(|s| format!("<{s}>"));
+/// assert_eq!(repeat_upper("ab", 2), "<ABAB>");
+/// ```
+pub fn pipe2<F, A1, A2, B>(f: F) -> impl FnOnce(A1, A2) -> B
+where
+    F: FnOnce(A1, A2) -> B,
+{
+    f
+}
+
+/// This is a convenience function to start a pipeline whose first stage takes
+/// three arguments. See the [`pipe2`] function for examples.
+pub fn pipe3<F, A1, A2, A3, B>(f: F) -> impl FnOnce(A1, A2, A3) -> B
+where
+    F: FnOnce(A1, A2, A3) -> B,
+{
+    f
+}
+
+/// Composes two functions in mathematical order, returning a function that
+/// runs `f`, then `g`. This is equivalent to `pipe(f).pipe(g)`.
+///
+/// ```
+/// # use pipe::*;
+/// let len_of_trimmed = compose(str::len, str::trim);
+/// assert_eq!(len_of_trimmed("  foo "), 3);
+/// ```
+pub fn compose<G, F, A, B, C>(g: G, f: F) -> impl FnOnce(A) -> C
+where
+    F: FnOnce(A) -> B,
+    G: FnOnce(B) -> C,
+{
+    |a| g(f(a))
+}
+
 /// The `pipe` macro provides an alternative syntax for constructing pipelines.
 ///
 /// The macro syntax is as follows:
 /// `<input identifier>: <first input type>; <pipe expression> => <pipe expression> => ... => <pipe expression>`
 ///
+/// Malformed input, such as a missing input type or a trailing `=>`, is
+/// reported with a compile error describing the expected syntax.
+///
 /// ```
 /// # use pipe::*;
 /// let remove_long_words = pipe! { this: &str;
@@ -62,15 +400,405 @@ where
 /// let short_words = remove_long_words("foo bar hello world baz");
 /// assert_eq!(short_words, "foo bar baz");
 /// ```
+///
+/// A pipeline can take several inputs by listing them in parentheses. The
+/// first stage may use every input, and the result of each stage is bound to
+/// the first identifier, while the others stay in scope:
+///
+/// ```
+/// # use pipe::*;
+/// let pad = pipe! { (s: &str, width: usize);
+///        format!("{s:>width$}")
+///     => s.replace(' ', ".")
+/// };
+/// assert_eq!(pad("foo", 5), "..foo");
+/// ```
+///
+/// Prefixing the input identifier with `try` and following its type with an
+/// error type builds a fallible pipeline. Each stage may then use the `?`
+/// operator, with errors converted into the given error type, and the result
+/// of the final stage is wrapped in `Ok`:
+///
+/// ```
+/// # use pipe::*;
+/// let parse_and_halve = pipe! { try this: &str => String;
+///        this.parse::<i32>().map_err(|e| e.to_string())?
+///     => if this % 2 == 0 { this / 2 } else { Err(format!("{this} is odd"))? }
+/// };
+/// assert_eq!(parse_and_halve("42"), Ok(21));
+/// assert_eq!(parse_and_halve("7"), Err("7 is odd".to_owned()));
+/// ```
+///
+/// Prefixing the input identifier with `async` builds an asynchronous
+/// pipeline, returning a future that evaluates the stages in order. Each stage
+/// may then use `.await`. The pipeline is expanded into an async closure, so
+/// captured values are borrowed by each future rather than moved into it, and
+/// the pipeline can be called more than once whenever its stages allow it:
+///
+/// ```
+/// # use pipe::*;
+/// # use pipe::__block_on as block_on;
+/// async fn lookup(id: u32) -> String {
+///     format!("user-{id}")
+/// }
+///
+/// let greet_user = pipe! { async this: u32;
+///        lookup(this).await
+///     => format!("hello, {this}")
+/// };
+/// assert_eq!(block_on(greet_user(7)), "hello, user-7");
+/// ```
+///
+/// A `tap` stage is a block run for its side effects only, with the identifier
+/// bound to a reference to the current value, which is then passed on
+/// unchanged. The block is required, so that a call to a function named `tap`
+/// is still an ordinary stage. Like the [`tap`] function, tap stages are
+/// compiled out when the `strip-taps` feature is enabled in builds without
+/// debug assertions:
+///
+/// ```
+/// # use pipe::*;
+/// let parse = pipe! { this: &str;
+///        this.split(',').collect::<Vec<_>>()
+///     => tap { println!("parts: {this:?}") }
+///     => this.len()
+/// };
+/// assert_eq!(parse("a,b"), 2);
+/// ```
+///
+/// Stages can be made conditional with the following keywords, each taking a
+/// condition in parentheses:
+///
+/// - `when (<condition>) <expression>` evaluates the expression only if the
+///   condition holds, and otherwise passes the value on unchanged, like the
+///   [`pipe_if`] function.
+/// - `branch (<condition>) <expression>, <expression>` evaluates the first
+///   expression if the condition holds and the second if it does not, like the
+///   [`branch`] function.
+/// - `split (<condition>) <expression>, <expression>` does the same, but wraps
+///   the result in [`Either::Left`] or [`Either::Right`], like the [`split`]
+///   function.
+///
+/// ```
+/// # use pipe::*;
+/// let collatz_step = pipe! { n: u64;
+///        when (n == 0) 1
+///     => branch (n % 2 == 0) n / 2, 3 * n + 1
+/// };
+/// assert_eq!(collatz_step(6), 3);
+/// assert_eq!(collatz_step(7), 22);
+/// ```
+///
+/// By default, every stage is bound to the input identifier. A stage can
+/// instead bind the current value to its own pattern, written like a closure
+/// parameter, which may destructure tuples and carry a type ascription. A
+/// return type may follow the pattern, with the stage written as a block:
+///
+/// ```
+/// # use pipe::*;
+/// let sum_and_product = pipe! { pair: (i32, i32);
+///        |(a, b)| (a + b, a * b)
+///     => |(sum, product): (i32, i32)| sum * 10 + product
+///     => |n| -> String { n.to_string() }
+/// };
+/// assert_eq!(sum_and_product((2, 3)), "56");
+/// ```
+///
+/// A `let` stage binds a pattern, optionally with a type, to the result of an
+/// expression, and passes the current value on unchanged. The binding stays in
+/// scope for every later stage, carrying earlier values forward:
+///
+/// ```
+/// # use pipe::*;
+/// let describe = pipe! { this: &str;
+///        let original = this
+///     => let words: usize = this.split_whitespace().count()
+///     => this.to_uppercase()
+///     => format!("{original} -> {this} ({words} words)")
+/// };
+/// assert_eq!(describe("hello world"), "hello world -> HELLO WORLD (2 words)");
+/// ```
+///
+/// A pipeline may also consist of a single stage:
+///
+/// ```
+/// # use pipe::*;
+/// let double = pipe! { this: i32; this * 2 };
+/// assert_eq!(double(21), 42);
+/// ```
+///
+/// The pipeline is expanded into a single closure, so it implements `Fn` or
+/// `FnMut` whenever its stages allow it and can be called more than once:
+///
+/// ```
+/// # use pipe::*;
+/// let double_then_inc = pipe! { this: i32; this * 2 => this + 1 };
+/// assert_eq!(double_then_inc(1), 3);
+/// assert_eq!(double_then_inc(2), 5);
+/// ```
 #[macro_export]
 macro_rules! pipe {
-    ( $ident:ident: $ty:ty; $first:expr => $( $rest:expr )=>* ) => {{
-        use $crate::Pipe;
-        $crate::pipe(|$ident: $ty| $first)
-        $(
-            .pipe(|$ident| $rest)
-        )+
+    ( async $ident:ident: $ty:ty; $( $stages:tt )* ) => {
+        $crate::pipe!(async ($ident: $ty); $( $stages )*)
+    };
+    ( async ( $ident:ident: $ty:ty $( , $idents:ident: $tys:ty )* $(,)? ); $( $stages:tt )* ) => {
+        async |$ident: $ty $( , $idents: $tys )*| { $crate::pipe!(@stages $ident; $( $stages )*) }
+    };
+    ( try $ident:ident: $ty:ty => $err:ty; $( $stages:tt )* ) => {
+        $crate::pipe!(try ($ident: $ty) => $err; $( $stages )*)
+    };
+    ( try ( $ident:ident: $ty:ty $( , $idents:ident: $tys:ty )* $(,)? ) => $err:ty; $( $stages:tt )* ) => {
+        |$ident: $ty $( , $idents: $tys )*| -> ::core::result::Result<_, $err> {
+            ::core::result::Result::Ok($crate::pipe!(@stages $ident; $( $stages )*))
+        }
+    };
+    ( $ident:ident: $ty:ty; $( $stages:tt )* ) => {
+        $crate::pipe!(($ident: $ty); $( $stages )*)
+    };
+    ( ( $ident:ident: $ty:ty $( , $idents:ident: $tys:ty )* $(,)? ); $( $stages:tt )* ) => {
+        |$ident: $ty $( , $idents: $tys )*| $crate::pipe!(@stages $ident; $( $stages )*)
+    };
+
+    ( @stages $ident:ident; ) => {
+        ::core::compile_error!("expected at least one stage after the pipeline input")
+    };
+    ( @stages $ident:ident; => $( $rest:tt )* ) => {
+        ::core::compile_error!("expected a stage before `=>`")
+    };
+    ( @stages $ident:ident; tap $tap:block $( => $( $rest:tt )+ )? ) => {{
+        if $crate::__TAPS_ENABLED {
+            let $ident = &$ident;
+            $tap;
+        }
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @stages $ident:ident; when ( $cond:expr ) $then:expr $( => $( $rest:tt )+ )? ) => {{
+        let $ident = if $cond { $then } else { $ident };
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @stages $ident:ident; branch ( $cond:expr ) $then:expr, $else:expr $( => $( $rest:tt )+ )? ) => {{
+        let $ident = if $cond { $then } else { $else };
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @stages $ident:ident; split ( $cond:expr ) $left:expr, $right:expr $( => $( $rest:tt )+ )? ) => {{
+        let $ident = if $cond {
+            $crate::Either::Left($left)
+        } else {
+            $crate::Either::Right($right)
+        };
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @stages $ident:ident; let $name:ident: $ty:ty = $value:expr $( => $( $rest:tt )+ )? ) => {{
+        let $name: $ty = $value;
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @stages $ident:ident; let $pat:pat_param = $value:expr $( => $( $rest:tt )+ )? ) => {{
+        let $pat = $value;
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @stages $ident:ident; let $( $tokens:tt )* ) => {
+        $crate::pipe!(@let $ident; []; $( $tokens )*)
+    };
+    ( @stages $ident:ident; | $( $tokens:tt )* ) => {
+        $crate::pipe!(@binding $ident; []; $( $tokens )*)
+    };
+    ( @stages $ident:ident; $stage:expr => ) => {
+        ::core::compile_error!("expected a stage after `=>`")
+    };
+    ( @stages $ident:ident; $stage:expr => $( $rest:tt )+ ) => {{
+        let $ident = $stage;
+        $crate::pipe!(@stages $ident; $( $rest )+)
+    }};
+    ( @stages $ident:ident; $stage:expr ) => {
+        $stage
+    };
+    ( @stages $ident:ident; $( $tokens:tt )* ) => {
+        ::core::compile_error!("expected a stage expression, followed by `=>` and the next stage")
+    };
+
+    // A pattern cannot be followed by a type ascription in a macro matcher, so
+    // typed `let` patterns are collected token by token up to the `:`.
+    ( @let $ident:ident; [ $( $pat:tt )* ]; : $ty:ty = $value:expr $( => $( $rest:tt )+ )? ) => {{
+        let $( $pat )*: $ty = $value;
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @let $ident:ident; [ $( $pat:tt )* ]; $( => $( $rest:tt )* )? ) => {
+        ::core::compile_error!("expected `=` and an expression after the `let` pattern")
+    };
+    ( @let $ident:ident; [ $( $pat:tt )* ]; $next:tt $( $tokens:tt )* ) => {
+        $crate::pipe!(@let $ident; [ $( $pat )* $next ]; $( $tokens )*)
+    };
+
+    ( @binding $ident:ident; [ $( $pat:tt )* ]; | -> $ty:ty $body:block $( => $( $rest:tt )+ )? ) => {{
+        let $ident: $ty = {
+            let $( $pat )* = $ident;
+            $body
+        };
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @binding $ident:ident; [ $( $pat:tt )* ]; | $( => $( $rest:tt )* )? ) => {
+        ::core::compile_error!("expected an expression after the stage binding")
+    };
+    ( @binding $ident:ident; [ $( $pat:tt )* ]; | $body:expr $( => $( $rest:tt )+ )? ) => {{
+        let $ident = {
+            let $( $pat )* = $ident;
+            $body
+        };
+        $crate::pipe!(@stages $ident; $ident $( => $( $rest )+ )?)
+    }};
+    ( @binding $ident:ident; [ $( $pat:tt )* ]; ) => {
+        ::core::compile_error!("expected a closing `|` after the stage binding")
+    };
+    ( @binding $ident:ident; [ $( $pat:tt )* ]; $next:tt $( $tokens:tt )* ) => {
+        $crate::pipe!(@binding $ident; [ $( $pat )* $next ]; $( $tokens )*)
+    };
+
+    ( $ident:ident; $( $tokens:tt )* ) => {
+        ::core::compile_error!("expected a type for the pipeline input, as in `this: Type;`")
+    };
+    ( $( $tokens:tt )* ) => {
+        ::core::compile_error!(
+            "expected a pipeline input such as `this: Type;` or `(a: A, b: B);`, followed by stages separated by `=>`"
+        )
+    };
+}
+
+/// The `pipe_value` macro applies a pipeline to a value immediately, instead
+/// of constructing a function. It is written as the input expression followed
+/// by stages separated by `=>`, and evaluates to the result of the last stage.
+///
+/// Each stage is either the path of a function, which is called with the
+/// current value, or an expression using the current value. The name of the
+/// current value is given before the input, as in `this = input`, and is
+/// rebound to the result of each stage. A stage made of that name alone passes
+/// the value on unchanged, and any other single identifier is called as a
+/// function. Without a name, only function paths can be used as stages:
+///
+/// ```
+/// # use pipe::*;
+/// fn shout(s: String) -> String {
+///     s.to_uppercase() + "!"
+/// }
+///
+/// let greeting = pipe_value!(this = "world" => format!("hello, {this}") => shout => this.len());
+/// assert_eq!(greeting, 13);
+///
+/// let abs = pipe_value!(-3 => i32::abs => i64::from);
+/// assert_eq!(abs, 3);
+/// ```
+///
+/// As the stages are evaluated in place, they may use the `?` operator and,
+/// inside async code, `.await`:
+///
+/// ```
+/// # use pipe::*;
+/// fn parse_doubled(s: &str) -> Result<i32, std::num::ParseIntError> {
+///     Ok(pipe_value!(n = s => str::trim => n.parse::<i32>()? => n * 2))
+/// }
+///
+/// assert_eq!(parse_doubled(" 21 "), Ok(42));
+/// assert!(parse_doubled("x").is_err());
+/// ```
+#[macro_export]
+macro_rules! pipe_value {
+    ( $this:ident = $input:expr => $( $stages:tt )+ ) => {{
+        let $this = $input;
+        $crate::pipe_value!(@stages $this; $( $stages )+)
+    }};
+    ( $input:expr => $( $stages:tt )+ ) => {{
+        let value = $input;
+        $crate::pipe_value!(@paths value; $( $stages )+)
+    }};
+    ( $input:expr ) => {
+        $input
+    };
+
+    ( @paths $value:ident; this $( $tokens:tt )* ) => {
+        ::core::compile_error!(
+            "stages using the current value need a name for it, as in `pipe_value!(this = input => ...)`"
+        )
+    };
+    ( @paths $value:ident; $f:path => $( $rest:tt )+ ) => {{
+        let $value = $f($value);
+        $crate::pipe_value!(@paths $value; $( $rest )+)
+    }};
+    ( @paths $value:ident; $f:path ) => {
+        $f($value)
+    };
+    ( @paths $value:ident; $( $tokens:tt )* ) => {
+        ::core::compile_error!(
+            "stages using the current value need a name for it, as in `pipe_value!(this = input => ...)`"
+        )
+    };
+
+    ( @stages $this:ident; $stage:ident => $( $rest:tt )+ ) => {{
+        let $this = $crate::pipe_value!(@ident $this; $stage);
+        $crate::pipe_value!(@stages $this; $( $rest )+)
+    }};
+    ( @stages $this:ident; $stage:ident ) => {
+        $crate::pipe_value!(@ident $this; $stage)
+    };
+    ( @stages $this:ident; $f:path => $( $rest:tt )+ ) => {{
+        let $this = $f($this);
+        $crate::pipe_value!(@stages $this; $( $rest )+)
+    }};
+    ( @stages $this:ident; $f:path ) => {
+        $f($this)
+    };
+    ( @stages $this:ident; $stage:expr => $( $rest:tt )+ ) => {{
+        let $this = $stage;
+        $crate::pipe_value!(@stages $this; $( $rest )+)
+    }};
+    ( @stages $this:ident; $stage:expr ) => {
+        $stage
+    };
+
+    // A single identifier is either the current value or a function to call
+    // with it, which is told apart by comparing it with the binding name.
+    ( @ident $this:ident; $stage:ident ) => {{
+        macro_rules! __pipe_value_ident {
+            ( $this ) => {
+                $this
+            };
+            ( $other:ident ) => {
+                $stage($this)
+            };
+        }
+        __pipe_value_ident!($stage)
+    }};
+}
+
+/// The `compose` macro builds a pipeline from a list of functions or
+/// closures, run from left to right. It behaves like starting a pipeline with
+/// [`pipe()`] and adding each following stage with [`Pipe::pipe`], without
+/// needing to name the value passed between stages.
+///
+/// ```
+/// # use pipe::*;
+/// fn parse(s: &str) -> i32 {
+///     s.trim().parse().unwrap()
+/// }
+///
+/// fn validate(n: i32) -> u32 {
+///     n.unsigned_abs()
+/// }
+///
+/// let normalize = compose![parse, validate, |n| n.min(100), |n| n.to_string()];
+/// assert_eq!(normalize(" -250 "), "100");
+/// ```
+///
+/// If the output of a stage does not match the input of the next one, the
+/// compiler reports a single error, pointing at the offending stage.
+#[macro_export]
+macro_rules! compose {
+    ( $first:expr $( , $stages:expr )* $(,)? ) => {{
+        let pipeline = $crate::__compose_first($first);
+        $( let pipeline = $crate::__compose_stage(pipeline, $stages); )*
+        $crate::__compose_finish(pipeline)
     }};
+    () => {
+        ::core::compile_error!("expected at least one function to compose")
+    };
 }
 
 #[cfg(test)]
@@ -104,4 +832,221 @@ mod tests {
             "hello - world - lorem - ipsum"
         );
     }
+
+    #[test]
+    fn test_pipe_mut_function() {
+        let mut total = 0;
+        let mut accumulate = pipe_mut(|s: &str| s.len()).pipe_mut(|len| {
+            total += len;
+            total
+        });
+        assert_eq!(accumulate("foo"), 3);
+        assert_eq!(accumulate("hello"), 8);
+        drop(accumulate);
+        assert_eq!(total, 8);
+    }
+
+    #[test]
+    fn test_pipe_fn_function() {
+        struct Handler<F> {
+            count_words: F,
+        }
+
+        let handler = Handler {
+            count_words: pipe_fn(|s: &str| s.split_whitespace())
+                .pipe_fn(|words| words.filter(|w| w.len() > 3))
+                .pipe_fn(|words| words.count()),
+        };
+        for _ in 0..3 {
+            assert_eq!((handler.count_words)("hello world foo lorem"), 3);
+        }
+    }
+
+    #[test]
+    fn test_pre_pipe() {
+        let describe = pipe(|n: i32| n * 2)
+            .pipe(|n| format!("<{n}>"))
+            .pre_pipe(|s: String| s.len() as i32)
+            .contramap(|words: Vec<&str>| words.join(" "));
+        assert_eq!(describe(vec!["foo", "bar"]), "<14>");
+    }
+
+    #[test]
+    fn test_dimap_and_compose() {
+        let count_words = pipe(|s: &str| s.split_whitespace().count());
+        let count_bytes_words = count_words.dimap(
+            |bytes: &[u8]| std::str::from_utf8(bytes).unwrap(),
+            |n| n * 10,
+        );
+        assert_eq!(count_bytes_words(b"foo bar baz"), 30);
+
+        let negate_len = compose(|n: usize| -(n as i64), |s: &str| s.len());
+        assert_eq!(negate_len("hello"), -5);
+    }
+
+    #[test]
+    fn test_pipe2_and_pipe3() {
+        fn area(w: u32, h: u32) -> u32 {
+            w * h
+        }
+
+        let describe = pipe2(area).pipe(|a| a * 2).pipe(|a| format!("{a} cm2"));
+        assert_eq!(describe(2, 3), "12 cm2");
+
+        let clamp = pipe3(|n: i32, lo: i32, hi: i32| n.max(lo).min(hi)).pipe(|n| n.to_string());
+        assert_eq!(clamp(12, 0, 10), "10");
+    }
+
+    #[test]
+    fn test_pipe_macro_multiple_inputs() {
+        let scale = pipe! { (n: i32, factor: i32);
+               n * factor
+            => n + factor
+            => n.to_string()
+        };
+        assert_eq!(scale(3, 4), "16");
+        assert_eq!(scale(2, 0), "0");
+
+        let parse_sum = pipe! { try (a: &str, b: &str,) => std::num::ParseIntError;
+               a.parse::<i32>()? + b.parse::<i32>()?
+        };
+        assert_eq!(parse_sum("1", "2"), Ok(3));
+        assert!(parse_sum("1", "x").is_err());
+    }
+
+    #[test]
+    fn test_pipe_macro_bindings() {
+        let stats = pipe! { this: Vec<i32>;
+               let len = this.len()
+            => |v| (v.iter().sum::<i32>(), v.into_iter().max())
+            => |(sum, max): (i32, Option<i32>)| -> (i32, i32) { (sum, max.unwrap_or(0)) }
+            => format!("{len} {} {}", this.0, this.1)
+        };
+        assert_eq!(stats(vec![1, 5, 3]), "3 9 5");
+        assert_eq!(stats(Vec::new()), "0 0 0");
+
+        let parse_pair = pipe! { try this: &str => String;
+               this.split_once(',').ok_or("missing comma")?
+            => let (left, right) = this
+            => |_| left.trim().parse::<i32>().map_err(|e| e.to_string())?
+            => this + right.trim().parse::<i32>().map_err(|e| e.to_string())?
+        };
+        assert_eq!(parse_pair("1, 2"), Ok(3));
+        assert_eq!(parse_pair("1"), Err("missing comma".to_owned()));
+
+        let describe = pipe! { this: (i32, i32);
+               let (a, b): (i32, i32) = this
+            => let [first, ..]: [i32; 2] = [a * 10, b]
+            => this.0 + b
+            => format!("{first} {this}")
+        };
+        assert_eq!(describe((1, 2)), "10 3");
+    }
+
+    #[test]
+    fn test_pipe_macro_single_stage() {
+        let len = pipe! { this: &str; this.len() };
+        assert_eq!(len("foo"), 3);
+
+        let swap = pipe! { pair: (i32, char); |(n, c)| (c, n) };
+        assert_eq!(swap((1, 'a')), ('a', 1));
+    }
+
+    #[test]
+    fn test_pipe_value_macro() {
+        fn words(s: &str) -> Vec<&str> {
+            s.split_whitespace().collect()
+        }
+
+        let mut calls = 0;
+        let longest = pipe_value!(this = "foo hello bar" => words => {
+            calls += 1;
+            this.into_iter().max_by_key(|w| w.len())
+        } => Option::unwrap_or_default);
+        assert_eq!(longest, "hello");
+        assert_eq!(calls, 1);
+
+        assert_eq!(pipe_value!(5), 5);
+        assert_eq!(pipe_value!(-3 => i32::abs => i64::from), 3i64);
+        assert_eq!(
+            pipe_value!(this = vec![3, 1, 2] => this => [this.len()]),
+            [3]
+        );
+    }
+
+    #[test]
+    fn test_pipe_value_macro_identity_stage() {
+        fn double(n: i32) -> i32 {
+            n * 2
+        }
+
+        let this = 100;
+        assert_eq!(pipe_value!(n = 5 => n), 5);
+        assert_eq!(pipe_value!(n = 5 => n => double => n + this), 110);
+
+        let this = |n: i32| n + 1;
+        assert_eq!(pipe_value!(n = 5 => this), 6);
+        assert_eq!(pipe_value!(this = 5 => this), 5);
+    }
+
+    #[test]
+    fn test_pipe_value_macro_try() {
+        fn parse_sum(a: &str, b: &str) -> Result<i32, std::num::ParseIntError> {
+            let sum = pipe_value!(pair = (a, b) => (pair.0.parse::<i32>()?, pair.1.parse::<i32>()?) => pair.0 + pair.1);
+            Ok(sum)
+        }
+
+        assert_eq!(parse_sum("1", "2"), Ok(3));
+        assert!(parse_sum("1", "b").is_err());
+    }
+
+    #[test]
+    fn test_pipe_value_macro_long_stages() {
+        let total = pipe_value!(this = 1 => {
+            let a = this + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10 + 11 + 12 + 13 + 14 + 15 + 16 + 17 + 18 + 19 + 20;
+            let b = a + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10 + 11 + 12 + 13 + 14 + 15 + 16 + 17 + 18 + 19 + 20;
+            let c = b + 1 + 2 + 3 + 4 + 5 + 6 + 7 + 8 + 9 + 10 + 11 + 12 + 13 + 14 + 15 + 16 + 17 + 18 + 19 + 20;
+            c - this
+        } => this * 2);
+        assert_eq!(total, 1260);
+    }
+
+    #[test]
+    fn test_compose_macro() {
+        fn words(s: &str) -> Vec<&str> {
+            s.split_whitespace().collect()
+        }
+
+        let count = compose![words, |w| w.len()];
+        assert_eq!(count("foo bar baz"), 3);
+
+        let single = compose![str::len,];
+        assert_eq!(single("foo"), 3);
+
+        let suffix = String::from("!");
+        let exclaim = compose![str::to_uppercase, move |s| s + &suffix, |s: String| s.len()];
+        assert_eq!(exclaim("hey"), 4);
+    }
+
+    #[test]
+    fn test_pipe_macro_reusable() {
+        fn call_twice(f: impl Fn(i32) -> i32) -> (i32, i32) {
+            (f(1), f(2))
+        }
+
+        let offset = 10;
+        assert_eq!(
+            call_twice(pipe! { this: i32; this * 2 => this + offset }),
+            (12, 14)
+        );
+
+        let mut calls = 0;
+        let mut counted = pipe! { this: i32;
+               this * 2
+            => { calls += 1; this }
+        };
+        assert_eq!(counted(3), 6);
+        assert_eq!(counted(4), 8);
+        assert_eq!(calls, 2);
+    }
 }
@@ -0,0 +1,204 @@
+use crate::{Stage, StageMut};
+use std::convert::Infallible;
+use std::fmt;
+
+type MutStage<T, E> = Box<dyn FnMut(&mut T) -> Result<(), E>>;
+
+/// A pipeline of stages that mutate a value in place, for processing large
+/// values step by step without moving them through every stage.
+///
+/// Stages are functions or closures taking a `&mut T`, added with
+/// [`pipe`](MutPipeline::pipe), or returning a `Result<(), E2>` for fallible
+/// stages, added with [`try_pipe`](MutPipeline::try_pipe). Running the
+/// pipeline stops at the first error, which is converted into `E` with
+/// [`From`]. Pipelines without fallible stages use [`Infallible`] as their
+/// error type.
+///
+/// ```
+/// # use pipe::*;
+/// #[derive(Default)]
+/// struct Report {
+///     lines: Vec<String>,
+///     total: usize,
+/// }
+///
+/// let mut finish = MutPipeline::<Report>::new()
+///     .pipe(|r| r.lines.retain(|l| !l.is_empty()))
+///     .pipe(|r| r.lines.sort())
+///     .pipe(|r| r.total = r.lines.len());
+///
+/// let mut report = Report {
+///     lines: vec!["b".to_owned(), String::new(), "a".to_owned()],
+///     ..Report::default()
+/// };
+/// let Ok(()) = finish.run(&mut report);
+/// assert_eq!(report.lines, ["a", "b"]);
+/// assert_eq!(report.total, 2);
+/// ```
+pub struct MutPipeline<T, E = Infallible> {
+    stages: Vec<MutStage<T, E>>,
+}
+
+impl<T, E> MutPipeline<T, E> {
+    /// Creates an empty pipeline.
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Adds a stage to the end of the pipeline, returning the pipeline.
+    pub fn pipe<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(&mut T) + 'static,
+    {
+        self.stages.push(Box::new(move |t| {
+            f(t);
+            Ok(())
+        }));
+        self
+    }
+
+    /// Adds a fallible stage to the end of the pipeline, returning the
+    /// pipeline. If the stage returns an error, later stages are skipped and
+    /// the error is converted into `E`.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let mut parse = MutPipeline::<Vec<String>, String>::new()
+    ///     .pipe(|v| v.iter_mut().for_each(|s| *s = s.trim().to_owned()))
+    ///     .try_pipe(|v| match v.iter().position(String::is_empty) {
+    ///         Some(i) => Err(format!("empty value at {i}")),
+    ///         None => Ok(()),
+    ///     });
+    ///
+    /// let mut values = vec![" a".to_owned(), " ".to_owned()];
+    /// assert_eq!(parse.run(&mut values), Err("empty value at 1".to_owned()));
+    /// assert_eq!(values, ["a", ""]);
+    /// ```
+    pub fn try_pipe<F, E2>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(&mut T) -> Result<(), E2> + 'static,
+        E: From<E2>,
+    {
+        self.stages.push(Box::new(move |t| Ok(f(t)?)));
+        self
+    }
+
+    /// Moves all stages of `other` to the end of this pipeline, leaving
+    /// `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        self.stages.append(&mut other.stages);
+    }
+
+    /// Returns the number of stages in the pipeline.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Returns `true` if the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Runs every stage on the given value in order, stopping at the first
+    /// error.
+    pub fn run(&mut self, value: &mut T) -> Result<(), E> {
+        self.stages.iter_mut().try_for_each(|stage| stage(value))
+    }
+
+    /// Runs every stage on the given value in order, returning the value once
+    /// all stages succeed.
+    pub fn apply(&mut self, mut value: T) -> Result<T, E> {
+        self.run(&mut value)?;
+        Ok(value)
+    }
+
+    /// Converts the pipeline into a closure running every stage on the value
+    /// it is given.
+    pub fn into_fn(mut self) -> impl FnMut(&mut T) -> Result<(), E> {
+        move |t| self.run(t)
+    }
+}
+
+impl<T, E> Default for MutPipeline<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E> fmt::Debug for MutPipeline<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MutPipeline")
+            .field("stages", &self.stages.len())
+            .finish()
+    }
+}
+
+impl<T, E> Stage<T> for MutPipeline<T, E> {
+    type Output = Result<T, E>;
+
+    fn run_once(mut self, input: T) -> Self::Output {
+        self.apply(input)
+    }
+}
+
+impl<T, E> StageMut<T> for MutPipeline<T, E> {
+    fn run_mut(&mut self, input: T) -> Self::Output {
+        self.apply(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[derive(Debug, PartialEq)]
+    enum ValidationError {
+        Negative(i64),
+        TooLarge,
+    }
+
+    #[test]
+    fn test_mut_pipeline_short_circuits() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let mut validate = MutPipeline::<Vec<i64>, ValidationError>::new()
+            .try_pipe(|v| match v.iter().find(|n| **n < 0) {
+                Some(n) => Err(ValidationError::Negative(*n)),
+                None => Ok(()),
+            })
+            .pipe(move |v| {
+                counter.set(counter.get() + 1);
+                v.iter_mut().for_each(|n| *n *= 2);
+            })
+            .try_pipe(|v| {
+                if v.iter().sum::<i64>() > 100 {
+                    Err(ValidationError::TooLarge)
+                } else {
+                    Ok(())
+                }
+            });
+
+        assert_eq!(validate.apply(vec![1, 2]), Ok(vec![2, 4]));
+        assert_eq!(
+            validate.apply(vec![1, -2]),
+            Err(ValidationError::Negative(-2))
+        );
+        assert_eq!(validate.apply(vec![60]), Err(ValidationError::TooLarge));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_mut_pipeline_append_and_stage() {
+        let mut first = MutPipeline::<String>::new().pipe(|s| s.push('a'));
+        let mut second = MutPipeline::new().pipe(|s: &mut String| s.push('b'));
+        first.append(&mut second);
+        assert_eq!(first.len(), 2);
+        assert!(second.is_empty());
+
+        let mut pipeline = Pipeline::new(first).then_mut(|s| s.map(|s| s.len()));
+        assert_eq!(pipeline.call_mut("x".to_owned()), Ok(3));
+        assert_eq!(pipeline.call_mut(String::new()), Ok(2));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Composed, Pipeline, Stage};
+use std::ops::{BitOr, Shl, Shr};
+
+/// Starts a [`Pipeline`] that can be composed with operators, as a shorthand
+/// for [`Pipeline::new`].
+///
+/// - `P(f) >> g` and `P(f) | g` run `f`, then `g`, like
+///   [`Pipeline::then`].
+/// - `P(f) << P(g)` runs `g`, then `f`. The right-hand side must be a
+///   pipeline, as the input type of a bare function could not be inferred.
+///
+/// Like the [`pipe`](fn@crate::pipe) function, only the input type of the first
+/// stage needs to be provided. Closures on the right-hand side of an operator
+/// must be wrapped in parentheses, as a closure body would otherwise extend
+/// over the rest of the expression.
+///
+/// ```
+/// # use pipe::*;
+/// let remove_long_words = P(|s: &str| s.split(' '))
+///     >> (|split| split.filter(|s| s.len() <= 4))
+///     >> (|filtered| filtered.collect::<Vec<_>>())
+///     >> (|words| words.join(" "));
+/// assert_eq!(remove_long_words.call("foo bar hello world baz"), "foo bar baz");
+/// ```
+#[allow(non_snake_case)]
+pub fn P<A, B, F>(f: F) -> Pipeline<A, F>
+where
+    F: Fn(A) -> B,
+{
+    Pipeline::new(f)
+}
+
+impl<A, F, G, C> Shr<G> for Pipeline<A, F>
+where
+    F: Stage<A>,
+    G: Fn(F::Output) -> C,
+{
+    type Output = Pipeline<A, Composed<F, G>>;
+
+    fn shr(self, g: G) -> Self::Output {
+        self.then(g)
+    }
+}
+
+impl<A, F, G, C> BitOr<G> for Pipeline<A, F>
+where
+    F: Stage<A>,
+    G: Fn(F::Output) -> C,
+{
+    type Output = Pipeline<A, Composed<F, G>>;
+
+    fn bitor(self, g: G) -> Self::Output {
+        self.then(g)
+    }
+}
+
+impl<A, Z, F, G> Shl<Pipeline<Z, G>> for Pipeline<A, F>
+where
+    F: Stage<A>,
+    G: Stage<Z, Output = A>,
+{
+    type Output = Pipeline<Z, Composed<Pipeline<Z, G>, Self>>;
+
+    fn shl(self, g: Pipeline<Z, G>) -> Self::Output {
+        Pipeline::new(Composed::new(g, self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_shr_inference() {
+        let parse_words = P(|s: &str| s.split(' '))
+            >> (|split| split.map(|s| s.trim()))
+            >> (|split| split.filter(|s| s.len() > 3))
+            >> (|split| split.collect::<Vec<_>>())
+            >> (|v| v.join(" - "));
+        assert_eq!(
+            parse_words.call("hello world foo lorem bar ipsum baz"),
+            "hello - world - lorem - ipsum"
+        );
+        assert_eq!(parse_words.call("foo bar"), "");
+    }
+
+    #[test]
+    fn test_bitor_matches_shr() {
+        fn inc(n: i32) -> i32 {
+            n + 1
+        }
+
+        let shr = P(|n: i32| n * 2) >> inc >> (|n| n.to_string());
+        let bitor = P(|n: i32| n * 2) | inc | (|n| n.to_string());
+        assert_eq!(shr.call(5), bitor.call(5));
+        assert_eq!(bitor.call(5), "11");
+    }
+
+    #[test]
+    fn test_shl_reverse_composition() {
+        let to_string = P(|n: usize| n.to_string());
+        let len_then_string = to_string << P(|s: &str| s.len());
+        assert_eq!(len_then_string.call("hello"), "5");
+
+        let exclaim = P(|s: String| s + "!") << P(|n: i32| n.to_string()) >> (|s| s.len());
+        assert_eq!(exclaim.call(50), 3);
+    }
+}
@@ -0,0 +1,207 @@
+use std::error::Error;
+use std::fmt;
+
+/// The `OptionPipe` trait composes pipelines whose stages return an `Option`.
+/// The pipeline halts at the first stage returning `None`, so later stages
+/// only ever see present values.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnOnce(A) -> Option<B>`, for any types `A` and `B`.
+pub trait OptionPipe<A, B> {
+    /// Wraps a function or closure returning an `Option` inside the currently
+    /// constructed pipeline. The stage is only called if every previous stage
+    /// returned `Some`.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let parse_value = pipe(|s: &str| s.split_once('='))
+    ///     .opt_pipe(|(_, value)| value.parse::<i32>().ok());
+    /// assert_eq!(parse_value("answer=42"), Some(42));
+    /// ```
+    fn opt_pipe<F, C>(self, f: F) -> impl FnOnce(A) -> Option<C>
+    where
+        F: FnOnce(B) -> Option<C>;
+
+    /// Wraps a function or closure that always produces a value inside the
+    /// currently constructed pipeline. The stage is only called if every
+    /// previous stage returned `Some`.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let key = pipe(|s: &str| s.split_once('=')).some_pipe(|(key, _)| key.trim());
+    /// assert_eq!(key(" answer =42"), Some("answer"));
+    /// ```
+    fn some_pipe<F, C>(self, f: F) -> impl FnOnce(A) -> Option<C>
+    where
+        F: FnOnce(B) -> C;
+
+    /// Starts tracing the currently constructed pipeline, so that a `None`
+    /// is reported as a [`NoneAt`] error naming the stage that produced it.
+    /// The current pipeline counts as stage `0`, and every stage added to the
+    /// returned [`TracedOptionPipe`] increases the index by one.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let parse_value = pipe(|s: &str| s.split_once('='))
+    ///     .traced()
+    ///     .opt_pipe(|(_, value)| value.parse::<i32>().ok())
+    ///     .some_pipe(|n| n * 2)
+    ///     .opt_pipe(|n| u8::try_from(n).ok());
+    /// assert_eq!(parse_value.call("answer=foo"), Err(NoneAt { stage: 1 }));
+    /// ```
+    fn traced(self) -> TracedOptionPipe<impl FnOnce(A) -> Result<B, NoneAt>>;
+}
+
+impl<F1, A, B> OptionPipe<A, B> for F1
+where
+    F1: FnOnce(A) -> Option<B>,
+{
+    fn opt_pipe<F2, C>(self, f: F2) -> impl FnOnce(A) -> Option<C>
+    where
+        F2: FnOnce(B) -> Option<C>,
+    {
+        |a| self(a).and_then(f)
+    }
+
+    fn some_pipe<F2, C>(self, f: F2) -> impl FnOnce(A) -> Option<C>
+    where
+        F2: FnOnce(B) -> C,
+    {
+        |a| self(a).map(f)
+    }
+
+    fn traced(self) -> TracedOptionPipe<impl FnOnce(A) -> Result<B, NoneAt>> {
+        TracedOptionPipe {
+            f: |a| self(a).ok_or(NoneAt { stage: 0 }),
+            stages: 1,
+        }
+    }
+}
+
+/// An option pipeline that keeps track of its stages, created by
+/// [`OptionPipe::traced`].
+pub struct TracedOptionPipe<F> {
+    f: F,
+    stages: usize,
+}
+
+impl<F> TracedOptionPipe<F> {
+    /// Wraps a function or closure returning an `Option` inside the currently
+    /// constructed pipeline. If it returns `None`, the pipeline fails with the
+    /// index of this stage.
+    pub fn opt_pipe<A, B, C, G>(self, g: G) -> TracedOptionPipe<impl FnOnce(A) -> Result<C, NoneAt>>
+    where
+        F: FnOnce(A) -> Result<B, NoneAt>,
+        G: FnOnce(B) -> Option<C>,
+    {
+        let stage = self.stages;
+        let f = self.f;
+        TracedOptionPipe {
+            f: move |a| g(f(a)?).ok_or(NoneAt { stage }),
+            stages: stage + 1,
+        }
+    }
+
+    /// Wraps a function or closure that always produces a value inside the
+    /// currently constructed pipeline.
+    pub fn some_pipe<A, B, C, G>(
+        self,
+        g: G,
+    ) -> TracedOptionPipe<impl FnOnce(A) -> Result<C, NoneAt>>
+    where
+        F: FnOnce(A) -> Result<B, NoneAt>,
+        G: FnOnce(B) -> C,
+    {
+        let f = self.f;
+        TracedOptionPipe {
+            f: move |a| f(a).map(g),
+            stages: self.stages + 1,
+        }
+    }
+
+    /// Returns the number of stages in the pipeline.
+    pub fn stages(&self) -> usize {
+        self.stages
+    }
+
+    /// Runs the pipeline with the given input.
+    pub fn call<A, B>(self, a: A) -> Result<B, NoneAt>
+    where
+        F: FnOnce(A) -> Result<B, NoneAt>,
+    {
+        (self.f)(a)
+    }
+
+    /// Returns the pipeline as a plain function, which can be extended further
+    /// using [`TryPipe`](crate::TryPipe).
+    pub fn into_fn<A, B>(self) -> F
+    where
+        F: FnOnce(A) -> Result<B, NoneAt>,
+    {
+        self.f
+    }
+}
+
+/// The error produced by a traced option pipeline when one of its stages
+/// returns `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoneAt {
+    /// The index of the stage that returned `None`, starting from `0`.
+    pub stage: usize,
+}
+
+impl fmt::Display for NoneAt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pipeline stage {} returned None", self.stage)
+    }
+}
+
+impl Error for NoneAt {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_opt_pipe_halts_at_none() {
+        let mut later_stage_called = false;
+        let parse = pipe(|s: &str| s.strip_prefix('#'))
+            .opt_pipe(|hex| u32::from_str_radix(hex, 16).ok())
+            .some_pipe(|n| {
+                later_stage_called = true;
+                n
+            });
+        assert_eq!(parse("ff0000"), None);
+        assert!(!later_stage_called);
+    }
+
+    #[test]
+    fn test_traced_reports_stage() {
+        let parse_color = || {
+            pipe(|s: &str| s.strip_prefix('#'))
+                .traced()
+                .opt_pipe(|hex| u32::from_str_radix(hex, 16).ok())
+                .some_pipe(|n| n.to_be_bytes())
+                .opt_pipe(|[alpha, r, g, b]| (alpha == 0).then_some((r, g, b)))
+        };
+        assert_eq!(parse_color().stages(), 4);
+        assert_eq!(parse_color().call("#ff8000"), Ok((0xff, 0x80, 0x00)));
+        assert_eq!(parse_color().call("ff8000"), Err(NoneAt { stage: 0 }));
+        assert_eq!(parse_color().call("#orange"), Err(NoneAt { stage: 1 }));
+        assert_eq!(parse_color().call("#1ff8000"), Err(NoneAt { stage: 3 }));
+        assert_eq!(
+            NoneAt { stage: 3 }.to_string(),
+            "pipeline stage 3 returned None"
+        );
+    }
+
+    #[test]
+    fn test_traced_into_fn() {
+        let parse = pipe(|s: &str| s.parse::<u8>().ok())
+            .traced()
+            .into_fn()
+            .err_into::<Box<dyn std::error::Error>>()
+            .try_pipe(|n| n.checked_add(200).ok_or("overflow"));
+        assert_eq!(parse("100").unwrap_err().to_string(), "overflow");
+    }
+}
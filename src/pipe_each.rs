@@ -0,0 +1,259 @@
+use crate::StageMut;
+use std::iter::FusedIterator;
+
+/// The `PipeEach` trait applies a reusable pipeline to every item of an
+/// iterator, lazily.
+///
+/// When in scope, this trait is implemented for all iterators. The pipeline
+/// may be anything implementing [`StageMut`], such as a pipeline built with
+/// [`pipe_mut`](crate::pipe_mut) or [`pipe_fn`](crate::pipe_fn), a
+/// [`Pipeline`](crate::Pipeline) or a [`Chain`](crate::Chain). As `StageMut`
+/// is not one of the `Fn*` traits, the argument types of a closure passed
+/// directly need to be annotated.
+pub trait PipeEach: Iterator + Sized {
+    /// Runs the pipeline on every item of the iterator, yielding its outputs.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let normalize = pipe_fn(|s: &str| s.trim()).pipe_fn(|s| s.to_lowercase());
+    /// let words = ["  Foo", "BAR "].into_iter().pipe_each(&normalize).collect::<Vec<_>>();
+    /// assert_eq!(words, ["foo", "bar"]);
+    /// ```
+    fn pipe_each<S>(self, stage: S) -> Piped<Self, S>
+    where
+        S: StageMut<Self::Item>;
+
+    /// Runs a fallible pipeline on every item of the iterator, yielding its
+    /// successful outputs. Errors are handled according to the given
+    /// [`ErrorPolicy`], and can be inspected on the returned adapter.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let parse = pipe_fn(|s: &str| s.trim()).pipe_fn(|s| s.parse::<i32>());
+    ///
+    /// let mut numbers = ["1", "x", "3", "y"].into_iter().try_pipe_each(&parse, ErrorPolicy::Collect);
+    /// assert_eq!(numbers.by_ref().collect::<Vec<_>>(), [1, 3]);
+    /// assert_eq!(numbers.errors().len(), 2);
+    ///
+    /// let mut numbers = ["1", "x", "3"].into_iter().try_pipe_each(&parse, ErrorPolicy::Stop);
+    /// assert_eq!(numbers.by_ref().collect::<Vec<_>>(), [1]);
+    /// assert!(numbers.error().is_some());
+    /// ```
+    fn try_pipe_each<S, B, E>(self, stage: S, policy: ErrorPolicy) -> TryPiped<Self, S, E>
+    where
+        S: StageMut<Self::Item, Output = Result<B, E>>;
+}
+
+impl<I: Iterator> PipeEach for I {
+    fn pipe_each<S>(self, stage: S) -> Piped<Self, S>
+    where
+        S: StageMut<Self::Item>,
+    {
+        Piped { iter: self, stage }
+    }
+
+    fn try_pipe_each<S, B, E>(self, stage: S, policy: ErrorPolicy) -> TryPiped<Self, S, E>
+    where
+        S: StageMut<Self::Item, Output = Result<B, E>>,
+    {
+        TryPiped {
+            iter: self,
+            stage,
+            policy,
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// An iterator running a pipeline on every item of another iterator, created
+/// by [`PipeEach::pipe_each`].
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Piped<I, S> {
+    iter: I,
+    stage: S,
+}
+
+impl<I, S> Iterator for Piped<I, S>
+where
+    I: Iterator,
+    S: StageMut<I::Item>,
+{
+    type Item = S::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|item| self.stage.run_mut(item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I, S> DoubleEndedIterator for Piped<I, S>
+where
+    I: DoubleEndedIterator,
+    S: StageMut<I::Item>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|item| self.stage.run_mut(item))
+    }
+}
+
+impl<I, S> ExactSizeIterator for Piped<I, S>
+where
+    I: ExactSizeIterator,
+    S: StageMut<I::Item>,
+{
+}
+
+impl<I, S> FusedIterator for Piped<I, S>
+where
+    I: FusedIterator,
+    S: StageMut<I::Item>,
+{
+}
+
+/// How a [`TryPiped`] iterator handles items for which the pipeline fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorPolicy {
+    /// Stop at the first error, keeping it.
+    Stop,
+    /// Skip items that fail, discarding their errors.
+    Skip,
+    /// Skip items that fail, keeping every error.
+    Collect,
+}
+
+/// An iterator running a fallible pipeline on every item of another iterator
+/// and yielding its successful outputs, created by
+/// [`PipeEach::try_pipe_each`].
+///
+/// Errors kept by the [`ErrorPolicy`] can be inspected with
+/// [`errors`](TryPiped::errors) once the iterator has been consumed, for
+/// instance through [`Iterator::by_ref`].
+#[derive(Debug, Clone)]
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct TryPiped<I, S, E> {
+    iter: I,
+    stage: S,
+    policy: ErrorPolicy,
+    errors: Vec<E>,
+}
+
+impl<I, S, E> TryPiped<I, S, E> {
+    /// Returns the policy for handling errors.
+    pub fn policy(&self) -> ErrorPolicy {
+        self.policy
+    }
+
+    /// Returns the errors kept so far, in the order they occurred.
+    pub fn errors(&self) -> &[E] {
+        &self.errors
+    }
+
+    /// Returns the first error kept so far, if any. With
+    /// [`ErrorPolicy::Stop`], this is the error the iterator stopped at.
+    pub fn error(&self) -> Option<&E> {
+        self.errors.first()
+    }
+
+    /// Consumes the iterator, returning the errors kept so far.
+    pub fn into_errors(self) -> Vec<E> {
+        self.errors
+    }
+}
+
+impl<I, S, B, E> Iterator for TryPiped<I, S, E>
+where
+    I: Iterator,
+    S: StageMut<I::Item, Output = Result<B, E>>,
+{
+    type Item = B;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.policy == ErrorPolicy::Stop && !self.errors.is_empty() {
+            return None;
+        }
+        for item in self.iter.by_ref() {
+            match self.stage.run_mut(item) {
+                Ok(out) => return Some(out),
+                Err(err) => match self.policy {
+                    ErrorPolicy::Stop => {
+                        self.errors.push(err);
+                        return None;
+                    }
+                    ErrorPolicy::Skip => {}
+                    ErrorPolicy::Collect => self.errors.push(err),
+                },
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.policy == ErrorPolicy::Stop && !self.errors.is_empty() {
+            (0, Some(0))
+        } else {
+            (0, self.iter.size_hint().1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug, PartialEq)]
+    struct OddError(i32);
+
+    fn halve(n: i32) -> Result<i32, OddError> {
+        if n % 2 == 0 {
+            Ok(n / 2)
+        } else {
+            Err(OddError(n))
+        }
+    }
+
+    #[test]
+    fn test_pipe_each_is_lazy() {
+        let mut calls = 0;
+        let mut piped = (1..4).pipe_each(pipe_mut(|n: i32| {
+            calls += 1;
+            n * 10
+        }));
+        assert_eq!(piped.len(), 3);
+        assert_eq!(piped.next_back(), Some(30));
+        assert_eq!(piped.next(), Some(10));
+        drop(piped);
+        assert_eq!(calls, 2);
+
+        let pipeline = Pipeline::new(|s: &str| s.len()).then(|n| n * 2);
+        let lengths = ["a", "bcd"]
+            .into_iter()
+            .pipe_each(pipeline)
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, [2, 6]);
+        assert_eq!(pipeline.call("ab"), 4);
+    }
+
+    #[test]
+    fn test_try_pipe_each_policies() {
+        let input = [2, 3, 4, 5, 6];
+
+        let mut stop = input.into_iter().try_pipe_each(halve, ErrorPolicy::Stop);
+        assert_eq!(stop.by_ref().collect::<Vec<_>>(), [1]);
+        assert_eq!(stop.next(), None);
+        assert_eq!(stop.into_errors(), [OddError(3)]);
+
+        let mut skip = input.into_iter().try_pipe_each(halve, ErrorPolicy::Skip);
+        assert_eq!(skip.by_ref().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(skip.errors().is_empty());
+
+        let mut collect = input.into_iter().try_pipe_each(halve, ErrorPolicy::Collect);
+        assert_eq!(collect.policy(), ErrorPolicy::Collect);
+        assert_eq!(collect.by_ref().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(collect.errors(), [OddError(3), OddError(5)]);
+        assert_eq!(collect.error(), Some(&OddError(3)));
+    }
+}
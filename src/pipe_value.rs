@@ -0,0 +1,140 @@
+/// The `PipeValue` trait applies functions to values eagerly, in method-chain
+/// style. Where [`Pipe`](crate::Pipe) builds a function to be called later,
+/// `x.pipe_into(f).pipe_into(g)` evaluates `g(f(x))` immediately.
+///
+/// This trait is implemented for all types, including closures, so its method
+/// names are chosen not to clash with the pipeline-building traits.
+///
+/// ```
+/// # use pipe::*;
+/// let short_words = "foo bar hello world baz"
+///     .pipe_into(|s| s.split(' '))
+///     .pipe_into(|split| split.filter(|s| s.len() <= 4))
+///     .pipe_into(|filtered| filtered.collect::<Vec<_>>())
+///     .pipe_into(|words| words.join(" "));
+/// assert_eq!(short_words, "foo bar baz");
+/// ```
+pub trait PipeValue {
+    /// Passes the value into the provided function or closure, returning its
+    /// result.
+    fn pipe_into<F, R>(self, f: F) -> R
+    where
+        Self: Sized,
+        F: FnOnce(Self) -> R,
+    {
+        f(self)
+    }
+
+    /// Passes a shared reference to the value into the provided function or
+    /// closure, returning its result.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let words = vec!["foo", "bar"];
+    /// assert_eq!(words.pipe_ref(|w| w.len()), 2);
+    /// assert_eq!(words, ["foo", "bar"]);
+    /// ```
+    fn pipe_ref<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Self) -> R,
+    {
+        f(self)
+    }
+
+    /// Passes a mutable reference to the value into the provided function or
+    /// closure, returning its result.
+    fn pipe_ref_mut<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        f(self)
+    }
+
+    /// Passes a shared reference to the value into the provided function or
+    /// closure, then returns the value itself. This is useful for inspecting
+    /// values in the middle of a chain.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let mut log = Vec::new();
+    /// let len = "foo bar"
+    ///     .tap_ref(|s| log.push(s.to_string()))
+    ///     .pipe_into(str::len);
+    /// assert_eq!(len, 7);
+    /// assert_eq!(log, ["foo bar"]);
+    /// ```
+    fn tap_ref<F>(self, f: F) -> Self
+    where
+        Self: Sized,
+        F: FnOnce(&Self),
+    {
+        f(&self);
+        self
+    }
+
+    /// Passes a mutable reference to the value into the provided function or
+    /// closure, then returns the value itself.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let sorted = vec![3, 1, 2].tap_ref_mut(|v| v.sort());
+    /// assert_eq!(sorted, [1, 2, 3]);
+    /// ```
+    fn tap_ref_mut<F>(mut self, f: F) -> Self
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self),
+    {
+        f(&mut self);
+        self
+    }
+}
+
+impl<T: ?Sized> PipeValue for T {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_pipe_value_chain() {
+        let parsed = " 42 "
+            .pipe_into(str::trim)
+            .pipe_into(str::parse::<i32>)
+            .pipe_into(Result::unwrap)
+            .pipe_into(|n| n * 2);
+        assert_eq!(parsed, 84);
+    }
+
+    #[test]
+    fn test_pipe_value_references() {
+        let mut words = vec!["foo".to_owned(), "hello".to_owned()];
+        let longest = words.pipe_ref(|w| w.iter().map(String::len).max());
+        assert_eq!(longest, Some(5));
+
+        let removed = words.pipe_ref_mut(|w| w.pop());
+        assert_eq!(removed.as_deref(), Some("hello"));
+        assert_eq!(words, ["foo"]);
+
+        let len = "bar".pipe_ref(|s| s.len());
+        assert_eq!(len, 3);
+    }
+
+    #[test]
+    fn test_pipe_value_tap() {
+        let mut seen = Vec::new();
+        let result = vec![3_usize, 1, 2]
+            .tap_ref(|v| seen.push(v.len()))
+            .tap_ref_mut(|v| v.sort())
+            .tap_ref(|v| seen.push(v[0]))
+            .pipe_into(|v| v.into_iter().sum::<usize>());
+        assert_eq!(result, 6);
+        assert_eq!(seen, [3, 1]);
+    }
+
+    #[test]
+    fn test_pipe_value_on_pipelines() {
+        let double = pipe(|n: i32| n * 2).pipe(|n| n + 1);
+        assert_eq!(5.pipe_into(double), 11);
+    }
+}
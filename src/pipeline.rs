@@ -0,0 +1,422 @@
+use std::marker::PhantomData;
+
+/// A single step of a [`Pipeline`] that can be called once.
+///
+/// This trait is implemented for all types implementing `FnOnce(A)`, as well
+/// as for [`Composed`] stages and nested [`Pipeline`]s. It stands in for the
+/// `FnOnce` trait, which cannot be implemented on stable Rust.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a pipeline stage taking `{A}`",
+    label = "expected a function, closure or pipeline taking `{A}`",
+    note = "each stage of a pipeline must take the output of the previous stage as its input"
+)]
+pub trait Stage<A> {
+    /// The output type of the stage.
+    type Output;
+
+    /// Runs the stage with the given input, consuming it.
+    fn run_once(self, input: A) -> Self::Output;
+}
+
+/// A [`Stage`] that can be called any number of times through a mutable
+/// reference, standing in for the `FnMut` trait.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a pipeline stage that can be run more than once with `{A}`",
+    label = "expected stages implementing `FnMut({A})`",
+    note = "a pipeline with a stage added by `then_once` can only be run with `call_once`"
+)]
+pub trait StageMut<A>: Stage<A> {
+    /// Runs the stage with the given input.
+    fn run_mut(&mut self, input: A) -> Self::Output;
+}
+
+/// A [`Stage`] that can be called any number of times through a shared
+/// reference, standing in for the `Fn` trait.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a pipeline stage that can be run through a shared reference with `{A}`",
+    label = "expected stages implementing `Fn({A})`",
+    note = "a pipeline with a stage added by `then_mut` can only be run with `call_mut` or `call_once`"
+)]
+pub trait StageFn<A>: StageMut<A> {
+    /// Runs the stage with the given input.
+    fn run(&self, input: A) -> Self::Output;
+}
+
+impl<F, A, B> Stage<A> for F
+where
+    F: FnOnce(A) -> B,
+{
+    type Output = B;
+
+    fn run_once(self, input: A) -> Self::Output {
+        self(input)
+    }
+}
+
+impl<F, A, B> StageMut<A> for F
+where
+    F: FnMut(A) -> B,
+{
+    fn run_mut(&mut self, input: A) -> Self::Output {
+        self(input)
+    }
+}
+
+impl<F, A, B> StageFn<A> for F
+where
+    F: Fn(A) -> B,
+{
+    fn run(&self, input: A) -> Self::Output {
+        self(input)
+    }
+}
+
+/// Two stages run one after the other, with the output of the first stage
+/// passed as input to the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Composed<F, G> {
+    pub(crate) first: F,
+    pub(crate) second: G,
+}
+
+impl<F, G> Composed<F, G> {
+    /// Composes the given stages, running `first` before `second`.
+    pub const fn new(first: F, second: G) -> Self {
+        Self { first, second }
+    }
+
+    /// Returns the composed stages.
+    pub fn into_inner(self) -> (F, G) {
+        (self.first, self.second)
+    }
+}
+
+impl<F, G, A> Stage<A> for Composed<F, G>
+where
+    F: Stage<A>,
+    G: Stage<F::Output>,
+{
+    type Output = G::Output;
+
+    fn run_once(self, input: A) -> Self::Output {
+        self.second.run_once(self.first.run_once(input))
+    }
+}
+
+impl<F, G, A> StageMut<A> for Composed<F, G>
+where
+    F: StageMut<A>,
+    G: StageMut<F::Output>,
+{
+    fn run_mut(&mut self, input: A) -> Self::Output {
+        self.second.run_mut(self.first.run_mut(input))
+    }
+}
+
+impl<F, G, A> StageFn<A> for Composed<F, G>
+where
+    F: StageFn<A>,
+    G: StageFn<F::Output>,
+{
+    fn run(&self, input: A) -> Self::Output {
+        self.second.run(self.first.run(input))
+    }
+}
+
+/// A stage that returns its input unchanged. It is the first stage of a
+/// pipeline created with [`Pipeline::start`] or [`start`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Identity;
+
+impl<A> Stage<A> for Identity {
+    type Output = A;
+
+    fn run_once(self, input: A) -> Self::Output {
+        input
+    }
+}
+
+impl<A> StageMut<A> for Identity {
+    fn run_mut(&mut self, input: A) -> Self::Output {
+        input
+    }
+}
+
+impl<A> StageFn<A> for Identity {
+    fn run(&self, input: A) -> Self::Output {
+        input
+    }
+}
+
+/// A pipeline with a nameable type, taking inputs of type `A` and running them
+/// through the stage `F`.
+///
+/// Unlike the functions returned by [`pipe`](fn@crate::pipe) and
+/// [`Pipe::pipe`](crate::Pipe::pipe), a `Pipeline` can be stored in struct
+/// fields, returned from trait methods and placed in `static`s. Because the
+/// `Fn*` traits cannot be implemented on stable Rust, it is run with the
+/// [`call_once`](Pipeline::call_once), [`call_mut`](Pipeline::call_mut) and
+/// [`call`](Pipeline::call) methods instead, or converted into a closure with
+/// [`into_fn`](Pipeline::into_fn) and friends.
+///
+/// ```
+/// # use pipe::*;
+/// fn trim(s: &str) -> &str {
+///     s.trim()
+/// }
+///
+/// fn len(s: &str) -> usize {
+///     s.len()
+/// }
+///
+/// type TrimmedLen = Pipeline<&'static str, Composed<fn(&str) -> &str, fn(&str) -> usize>>;
+///
+/// static TRIMMED_LEN: TrimmedLen = Pipeline::new(Composed::new(trim as _, len as _));
+///
+/// assert_eq!(TRIMMED_LEN.call("  foo "), 3);
+/// assert_eq!(TRIMMED_LEN.call("hello"), 5);
+/// ```
+pub struct Pipeline<A, F = Identity> {
+    stage: F,
+    input: PhantomData<fn(A)>,
+}
+
+impl<A> Pipeline<A> {
+    /// Creates an empty pipeline taking inputs of type `A`, which returns them
+    /// unchanged until stages are added. As the input type is declared up
+    /// front, the argument types of every following stage can be inferred,
+    /// including for borrowed inputs.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let first_word_len = Pipeline::<&str>::start()
+    ///     .then(|s| s.split_whitespace().next())
+    ///     .then(|word| word.map_or(0, str::len));
+    /// assert_eq!(first_word_len.call("hello world"), 5);
+    /// ```
+    pub const fn start() -> Self {
+        Self::new(Identity)
+    }
+}
+
+impl<A, F> Pipeline<A, F> {
+    /// Creates a pipeline from its first stage.
+    pub const fn new(stage: F) -> Self
+    where
+        F: Stage<A>,
+    {
+        Self {
+            stage,
+            input: PhantomData,
+        }
+    }
+
+    /// Adds the provided function or closure as the last stage of the
+    /// pipeline. The stage must implement `Fn`, so that the pipeline can be
+    /// run with [`call`](Pipeline::call).
+    pub fn then<G, C>(self, g: G) -> Pipeline<A, Composed<F, G>>
+    where
+        F: Stage<A>,
+        G: Fn(F::Output) -> C,
+    {
+        Pipeline::new(Composed::new(self.stage, g))
+    }
+
+    /// Adds the provided function or closure as the last stage of the
+    /// pipeline. The stage must implement `FnMut`, so that the pipeline can be
+    /// run with [`call_mut`](Pipeline::call_mut).
+    pub fn then_mut<G, C>(self, g: G) -> Pipeline<A, Composed<F, G>>
+    where
+        F: Stage<A>,
+        G: FnMut(F::Output) -> C,
+    {
+        Pipeline::new(Composed::new(self.stage, g))
+    }
+
+    /// Adds the provided function or closure as the last stage of the
+    /// pipeline. The stage only needs to implement `FnOnce`, so the pipeline
+    /// can only be run with [`call_once`](Pipeline::call_once).
+    pub fn then_once<G, C>(self, g: G) -> Pipeline<A, Composed<F, G>>
+    where
+        F: Stage<A>,
+        G: FnOnce(F::Output) -> C,
+    {
+        Pipeline::new(Composed::new(self.stage, g))
+    }
+
+    /// Runs the pipeline with the given input, consuming it.
+    pub fn call_once(self, input: A) -> F::Output
+    where
+        F: Stage<A>,
+    {
+        self.stage.run_once(input)
+    }
+
+    /// Runs the pipeline with the given input.
+    pub fn call_mut(&mut self, input: A) -> F::Output
+    where
+        F: StageMut<A>,
+    {
+        self.stage.run_mut(input)
+    }
+
+    /// Runs the pipeline with the given input.
+    pub fn call(&self, input: A) -> F::Output
+    where
+        F: StageFn<A>,
+    {
+        self.stage.run(input)
+    }
+
+    /// Converts the pipeline into a closure that can be called once.
+    pub fn into_fn_once(self) -> impl FnOnce(A) -> F::Output
+    where
+        F: Stage<A>,
+    {
+        move |a| self.call_once(a)
+    }
+
+    /// Converts the pipeline into a closure that can be called any number of
+    /// times through a mutable reference.
+    pub fn into_fn_mut(mut self) -> impl FnMut(A) -> F::Output
+    where
+        F: StageMut<A>,
+    {
+        move |a| self.call_mut(a)
+    }
+
+    /// Converts the pipeline into a closure that can be called any number of
+    /// times through a shared reference.
+    pub fn into_fn(self) -> impl Fn(A) -> F::Output
+    where
+        F: StageFn<A>,
+    {
+        move |a| self.call(a)
+    }
+
+    /// Returns the stage the pipeline runs.
+    pub fn into_inner(self) -> F {
+        self.stage
+    }
+}
+
+impl<A, F: Clone> Clone for Pipeline<A, F> {
+    fn clone(&self) -> Self {
+        Self {
+            stage: self.stage.clone(),
+            input: PhantomData,
+        }
+    }
+}
+
+impl<A, F: Copy> Copy for Pipeline<A, F> {}
+
+impl<A, F> Stage<A> for Pipeline<A, F>
+where
+    F: Stage<A>,
+{
+    type Output = F::Output;
+
+    fn run_once(self, input: A) -> Self::Output {
+        self.call_once(input)
+    }
+}
+
+impl<A, F> StageMut<A> for Pipeline<A, F>
+where
+    F: StageMut<A>,
+{
+    fn run_mut(&mut self, input: A) -> Self::Output {
+        self.call_mut(input)
+    }
+}
+
+impl<A, F> StageFn<A> for Pipeline<A, F>
+where
+    F: StageFn<A>,
+{
+    fn run(&self, input: A) -> Self::Output {
+        self.call(input)
+    }
+}
+
+/// Creates an empty pipeline taking inputs of type `T`. This is a shorthand
+/// for [`Pipeline::start`].
+///
+/// ```
+/// # use pipe::*;
+/// let parse_sum = start::<&str>()
+///     .then(|s| s.split(',').map(|n| n.trim().parse::<i32>().unwrap()))
+///     .then(|numbers| numbers.sum::<i32>());
+/// assert_eq!(parse_sum.call("1, 2, 3"), 6);
+/// ```
+pub const fn start<T>() -> Pipeline<T> {
+    Pipeline::start()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_pipeline_inference() {
+        let longest_word = Pipeline::new(|s: &str| s.to_lowercase())
+            .then(|s| s.split(' ').map(str::len).max())
+            .then(|len| len.unwrap_or_default());
+        assert_eq!(longest_word.call("hello world foo"), 5);
+        assert_eq!(longest_word.call(""), 0);
+    }
+
+    #[test]
+    fn test_pipeline_struct_field() {
+        struct Counter<F> {
+            pipeline: Pipeline<i32, F>,
+        }
+
+        impl<F: StageMut<i32, Output = i32>> Counter<F> {
+            fn feed(&mut self, n: i32) -> i32 {
+                self.pipeline.call_mut(n)
+            }
+        }
+
+        let mut total = 0;
+        let mut counter = Counter {
+            pipeline: Pipeline::new(|n: i32| n * 2).then_mut(|n| {
+                total += n;
+                total
+            }),
+        };
+        assert_eq!(counter.feed(1), 2);
+        assert_eq!(counter.feed(2), 6);
+    }
+
+    #[test]
+    fn test_pipeline_nested() {
+        let inner = Pipeline::new(|n: i32| n + 1).then(|n| n * 2);
+        let outer = Pipeline::new(inner).then(|n| n.to_string());
+        assert_eq!(outer.call(1), "4");
+
+        let as_fn = outer.into_fn();
+        assert_eq!(["1", "2"].map(|s| as_fn(s.parse().unwrap())), ["4", "6"]);
+    }
+
+    #[test]
+    fn test_pipeline_start_borrowed() {
+        let text = String::from("  Hello World  ");
+        let words = start::<&str>()
+            .then(|s| s.trim())
+            .then(|s| s.split(' ').collect::<Vec<_>>());
+        assert_eq!(words.call(&text), ["Hello", "World"]);
+
+        let identity = Pipeline::<Vec<i32>>::start();
+        assert_eq!(identity.call(vec![1, 2]), [1, 2]);
+    }
+
+    #[test]
+    fn test_pipeline_call_once() {
+        let name = String::from("foo");
+        let greet = Pipeline::new(move |greeting: &str| format!("{greeting}, {name}"))
+            .then_once(|s| s.to_uppercase());
+        assert_eq!(greet.call_once("hello"), "HELLO, FOO");
+    }
+}
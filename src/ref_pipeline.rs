@@ -0,0 +1,211 @@
+use crate::{Composed, Identity};
+use std::marker::PhantomData;
+
+/// A single step of a [`RefPipeline`], borrowing its output from its input.
+///
+/// This trait is implemented for all types implementing
+/// `for<'a> Fn(&'a A) -> &'a B`, as well as for [`Composed`] stages, the
+/// [`Identity`] stage and nested [`RefPipeline`]s. Unlike [`Stage`](crate::Stage),
+/// the lifetime of the input is not fixed when the stage is created, so a
+/// single stage can be run with inputs of any lifetime.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a borrowed pipeline stage taking `&{A}`",
+    label = "expected a function or closure implementing `for<'a> Fn(&'a {A}) -> &'a _`",
+    note = "each stage of a borrowed pipeline must return a reference borrowed from its input"
+)]
+pub trait RefStage<A: ?Sized> {
+    /// The type the output of the stage refers to.
+    type Output: ?Sized;
+
+    /// Runs the stage with the given input.
+    fn run_ref<'a>(&self, input: &'a A) -> &'a Self::Output;
+}
+
+impl<F, A, B> RefStage<A> for F
+where
+    F: for<'a> Fn(&'a A) -> &'a B,
+    A: ?Sized,
+    B: ?Sized,
+{
+    type Output = B;
+
+    fn run_ref<'a>(&self, input: &'a A) -> &'a Self::Output {
+        self(input)
+    }
+}
+
+impl<A: ?Sized> RefStage<A> for Identity {
+    type Output = A;
+
+    fn run_ref<'a>(&self, input: &'a A) -> &'a Self::Output {
+        input
+    }
+}
+
+impl<F, G, A> RefStage<A> for Composed<F, G>
+where
+    F: RefStage<A>,
+    F::Output: 'static,
+    G: RefStage<F::Output>,
+    A: ?Sized,
+{
+    type Output = G::Output;
+
+    fn run_ref<'a>(&self, input: &'a A) -> &'a Self::Output {
+        self.second.run_ref(self.first.run_ref(input))
+    }
+}
+
+/// A pipeline over borrowed values, taking a `&A` and returning a reference
+/// borrowed from it, running it through the stage `F`.
+///
+/// Pipelines built with [`Pipe::pipe`](crate::Pipe::pipe) or
+/// [`Pipeline`](crate::Pipeline) fix the lifetime of their input when they are
+/// created. A `RefPipeline` keeps every stage higher-ranked instead, so it can
+/// be stored once and run with inputs of any lifetime, with the output
+/// borrowing from the input without copying it.
+///
+/// ```
+/// # use pipe::*;
+/// struct Parser<F> {
+///     value: RefPipeline<str, F>,
+/// }
+///
+/// let parser = Parser {
+///     value: RefPipeline::<str>::start()
+///         .then(str::trim)
+///         .then(|s| s.strip_prefix('"').unwrap_or(s))
+///         .then(|s| s.strip_suffix('"').unwrap_or(s)),
+/// };
+///
+/// for line in [String::from(" \"foo\" "), String::from("bar")] {
+///     let value: &str = parser.value.call(&line);
+///     assert!(line.contains(value));
+/// }
+/// ```
+pub struct RefPipeline<A: ?Sized, F = Identity> {
+    stage: F,
+    input: PhantomData<fn(&A)>,
+}
+
+impl<A: ?Sized> RefPipeline<A> {
+    /// Creates an empty pipeline taking inputs of type `&A`, which returns
+    /// them unchanged until stages are added.
+    pub const fn start() -> Self {
+        Self::new(Identity)
+    }
+}
+
+impl<A: ?Sized, F> RefPipeline<A, F> {
+    /// Creates a pipeline from its first stage.
+    pub const fn new(stage: F) -> Self
+    where
+        F: RefStage<A>,
+    {
+        Self {
+            stage,
+            input: PhantomData,
+        }
+    }
+
+    /// Adds the provided function or closure as the last stage of the
+    /// pipeline. The stage must return a reference borrowed from its input.
+    ///
+    /// The type the previous stage's output refers to, such as `str` or
+    /// `[u8]`, must not itself contain borrowed data.
+    pub fn then<G, C>(self, g: G) -> RefPipeline<A, Composed<F, G>>
+    where
+        F: RefStage<A>,
+        F::Output: 'static,
+        G: for<'a> Fn(&'a F::Output) -> &'a C,
+        C: ?Sized,
+    {
+        RefPipeline::new(Composed::new(self.stage, g))
+    }
+
+    /// Runs the pipeline with the given input.
+    pub fn call<'a>(&self, input: &'a A) -> &'a F::Output
+    where
+        F: RefStage<A>,
+    {
+        self.stage.run_ref(input)
+    }
+
+    /// Converts the pipeline into a closure that can be called with inputs
+    /// of any lifetime.
+    pub fn into_fn(self) -> impl for<'a> Fn(&'a A) -> &'a F::Output
+    where
+        F: RefStage<A>,
+    {
+        move |a| self.call(a)
+    }
+
+    /// Returns the stage the pipeline runs.
+    pub fn into_inner(self) -> F {
+        self.stage
+    }
+}
+
+impl<A: ?Sized, F: Clone> Clone for RefPipeline<A, F> {
+    fn clone(&self) -> Self {
+        Self {
+            stage: self.stage.clone(),
+            input: PhantomData,
+        }
+    }
+}
+
+impl<A: ?Sized, F: Copy> Copy for RefPipeline<A, F> {}
+
+impl<A, F> RefStage<A> for RefPipeline<A, F>
+where
+    F: RefStage<A>,
+    A: ?Sized,
+{
+    type Output = F::Output;
+
+    fn run_ref<'a>(&self, input: &'a A) -> &'a Self::Output {
+        self.call(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn first_field(line: &str) -> &str {
+        line.split(',').next().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_ref_pipeline_any_lifetime() {
+        let key = RefPipeline::new(first_field).then(str::trim);
+
+        let owned = String::from(" foo , bar");
+        assert_eq!(key.call(&owned), "foo");
+
+        let results: Vec<&str> = {
+            let lines = [String::from("a,1"), String::from(" b ,2")];
+            let keys = lines.iter().map(|l| key.call(l)).collect::<Vec<_>>();
+            assert_eq!(keys, ["a", "b"]);
+            vec![key.call("static")]
+        };
+        assert_eq!(results, ["static"]);
+    }
+
+    #[test]
+    fn test_ref_pipeline_slices() {
+        let middle = RefPipeline::<[i32]>::start()
+            .then(|v| v.get(1..).unwrap_or_default())
+            .then(|v| &v[..v.len().saturating_sub(1)]);
+        assert_eq!(middle.call(&[1, 2, 3, 4]), [2, 3]);
+        assert_eq!(middle.call(&[]), []);
+
+        let nested = RefPipeline::new(middle).then(|v| v.first().unwrap_or(&0));
+        assert_eq!(*nested.call(&[1, 2, 3]), 2);
+
+        let as_fn = nested.into_fn();
+        let data = vec![5, 6, 7];
+        assert_eq!(*as_fn(&data), 6);
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Debug;
+use std::panic::Location;
+
+/// Whether tap stages run. Taps are only compiled out when the `strip-taps`
+/// feature is enabled in a build without debug assertions.
+#[doc(hidden)]
+pub const __TAPS_ENABLED: bool = !cfg!(feature = "strip-taps") || cfg!(debug_assertions);
+
+/// Creates a pipeline stage that passes a shared reference to its input into
+/// the provided function or closure, then returns the input unchanged. This is
+/// useful for logging or debugging values in the middle of a pipeline.
+///
+/// The returned stage implements `Fn`, so it can be used in any kind of
+/// pipeline. The `pipe!` macro provides the same stage through its `tap`
+/// keyword, whose block may also record what it sees.
+///
+/// When the `strip-taps` feature is enabled, tap stages do nothing in builds
+/// without debug assertions, such as release builds.
+///
+/// ```
+/// # use pipe::*;
+/// let parse = pipe_fn(|s: &str| s.trim())
+///     .pipe_fn(tap(|s: &&str| println!("parsing {s:?}")))
+///     .pipe_fn(|s| s.parse::<i32>());
+/// assert_eq!(parse(" 42 "), Ok(42));
+/// assert_eq!(parse("7"), Ok(7));
+/// ```
+pub fn tap<B, F>(f: F) -> impl Fn(B) -> B
+where
+    F: Fn(&B),
+{
+    move |b| {
+        if __TAPS_ENABLED {
+            f(&b);
+        }
+        b
+    }
+}
+
+/// Creates a pipeline stage that passes a mutable reference to its input into
+/// the provided function or closure, then returns the input. This adjusts a
+/// value in place without rebuilding it, which is useful for values that are
+/// not `Copy`.
+///
+/// Unlike [`tap`], this stage changes the value passed on, so it is never
+/// compiled out by the `strip-taps` feature.
+///
+/// ```
+/// # use pipe::*;
+/// let sorted = pipe(|s: &str| s.split(' ').collect::<Vec<_>>())
+///     .pipe(tap_mut(|words: &mut Vec<&str>| words.sort()));
+/// assert_eq!(sorted("foo bar baz"), ["bar", "baz", "foo"]);
+/// ```
+pub fn tap_mut<B, F>(mut f: F) -> impl FnMut(B) -> B
+where
+    F: FnMut(&mut B),
+{
+    move |mut b| {
+        f(&mut b);
+        b
+    }
+}
+
+/// Creates a pipeline stage that prints its input to standard error, in the
+/// same format as the `dbg!` macro, then returns it unchanged. The printed
+/// location is where the stage was created. Like [`tap`], it is compiled out
+/// when the `strip-taps` feature is enabled in builds without debug
+/// assertions.
+///
+/// ```
+/// # use pipe::*;
+/// let parse = pipe(|s: &str| s.split(',').collect::<Vec<_>>())
+///     .pipe(inspect_dbg())
+///     .pipe(|parts| parts.len());
+/// assert_eq!(parse("a,b"), 2);
+/// ```
+#[track_caller]
+pub fn inspect_dbg<B>() -> impl Fn(B) -> B
+where
+    B: Debug,
+{
+    let location = Location::caller();
+    move |b| {
+        if __TAPS_ENABLED {
+            eprintln!(
+                "[{}:{}:{}] {:#?}",
+                location.file(),
+                location.line(),
+                location.column(),
+                b
+            );
+        }
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::__TAPS_ENABLED;
+    use crate::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_tap_in_fn_pipeline() {
+        let seen = Cell::new(0);
+        let parse = pipe_fn(|s: &str| s.split(' ').map(str::to_owned).collect::<Vec<_>>())
+            .pipe_fn(tap(|words: &Vec<String>| {
+                seen.set(seen.get() + words.len())
+            }))
+            .pipe_fn(inspect_dbg())
+            .pipe_fn(|words| words.concat());
+        assert_eq!(parse("foo bar"), "foobar");
+        assert_eq!(parse("baz"), "baz");
+        let expected = if __TAPS_ENABLED { 3 } else { 0 };
+        assert_eq!(seen.get(), expected);
+    }
+
+    #[test]
+    fn test_tap_mut_records() {
+        let mut seen = Vec::new();
+        let mut parse = pipe_mut(|s: &str| s.split(' ').map(str::to_owned).collect::<Vec<_>>())
+            .pipe_mut(tap_mut(|words: &mut Vec<String>| seen.push(words.len())))
+            .pipe_mut(inspect_dbg())
+            .pipe_mut(|words| words.concat());
+        assert_eq!(parse("foo bar"), "foobar");
+        assert_eq!(parse("baz"), "baz");
+        drop(parse);
+        assert_eq!(seen, [2, 1]);
+    }
+
+    #[test]
+    fn test_tap_mut_in_pipeline() {
+        let mut calls = 0;
+        let mut dedup = Pipeline::new(|s: &str| s.chars().collect::<Vec<_>>())
+            .then_mut(tap_mut(|chars: &mut Vec<char>| {
+                calls += 1;
+                chars.dedup();
+            }))
+            .then(|chars| chars.into_iter().collect::<String>());
+        assert_eq!(dedup.call_mut("aabbbc"), "abc");
+        assert_eq!(dedup.call_mut("xxy"), "xy");
+        drop(dedup);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_tap_macro() {
+        let mut seen = Vec::new();
+        let mut parse = pipe! { this: &str;
+               this.split(' ').map(str::to_owned).collect::<Vec<_>>()
+            => tap { seen.extend(this.iter().cloned()) }
+            => this.len()
+            => tap { seen.push(this.to_string()); }
+        };
+        assert_eq!(parse("foo bar"), 2);
+        let expected: &[&str] = if __TAPS_ENABLED {
+            &["foo", "bar", "2"]
+        } else {
+            &[]
+        };
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn test_tap_function_in_macro() {
+        fn tap(n: usize) -> usize {
+            n + 1
+        }
+
+        let count = pipe! { this: &str; this.split(' ').count() => tap(this) };
+        assert_eq!(count("a b c"), 4);
+        let count = pipe! { this: &str; this.split(' ').count() => tap (this) * 2 };
+        assert_eq!(count("a b c"), 8);
+    }
+}
@@ -0,0 +1,25 @@
+//! Helpers for testing asynchronous pipelines without depending on a runtime.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pub use crate::__block_on as block_on;
+
+/// A future that is pending once before completing, so that tests exercise
+/// more than the first poll.
+pub struct YieldNow(pub bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
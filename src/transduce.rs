@@ -0,0 +1,571 @@
+use crate::Composed;
+use std::marker::PhantomData;
+use std::mem;
+
+/// The result of a reducing step, telling the process running the reducer
+/// whether to continue with the next item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step<Acc> {
+    /// The accumulated value, with more items accepted.
+    Continue(Acc),
+    /// The final accumulated value. No more items should be passed to the
+    /// reducer, which allows processes to stop early.
+    Done(Acc),
+}
+
+impl<Acc> Step<Acc> {
+    /// Returns `true` if the step is [`Step::Done`].
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Done(_))
+    }
+
+    /// Returns the accumulated value.
+    pub fn into_inner(self) -> Acc {
+        match self {
+            Self::Continue(acc) | Self::Done(acc) => acc,
+        }
+    }
+}
+
+/// A reducing step, combining an accumulated value with an item.
+///
+/// This trait is implemented for all types implementing
+/// `FnMut(Acc, T) -> Acc`, which always continue. Transducers wrap reducers
+/// in reducers of their own, which may stop early or hold items until the
+/// process completes.
+pub trait Reducer<Acc, T> {
+    /// Combines the accumulated value with the next item.
+    fn step(&mut self, acc: Acc, item: T) -> Step<Acc>;
+
+    /// Finishes the accumulated value once there are no more items, or the
+    /// process stopped early. By default, the value is returned unchanged.
+    fn complete(&mut self, acc: Acc) -> Acc {
+        acc
+    }
+
+    /// Returns `true` if the reducer accepts no more items, so that processes
+    /// can stop before taking the next one from their source. By default,
+    /// more items are always accepted.
+    fn is_done(&self) -> bool {
+        false
+    }
+}
+
+impl<F, Acc, T> Reducer<Acc, T> for F
+where
+    F: FnMut(Acc, T) -> Acc,
+{
+    fn step(&mut self, acc: Acc, item: T) -> Step<Acc> {
+        Step::Continue(self(acc, item))
+    }
+}
+
+/// A transformation of reducing steps taking items of type `A`, independent
+/// of where the items come from and how they are accumulated.
+///
+/// A transducer turns a reducer of its [`Output`](Transducer::Output) items
+/// into a reducer of `A` items. It can be run over iterators and channels
+/// with [`transduce`], and over asynchronous sources with
+/// [`transduce_async`]. Transducers are composed with
+/// [`pipe`](Transducer::pipe), with items flowing from left to right.
+pub trait Transducer<A> {
+    /// The type of the items passed on to the wrapped reducer.
+    type Output;
+
+    /// Wraps the provided reducer, returning a reducer of `A` items.
+    fn apply<Acc, R>(self, reducer: R) -> impl Reducer<Acc, A>
+    where
+        R: Reducer<Acc, Self::Output>;
+
+    /// Composes this transducer with the next one, which receives the items
+    /// this transducer passes on.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let xform = filtering(|n: &i32| n % 2 == 1)
+    ///     .pipe(mapping(|n: i32| n * n))
+    ///     .pipe(taking(3));
+    /// let sum = transduce(1.., xform, 0, |acc, n| acc + n);
+    /// assert_eq!(sum, 1 + 9 + 25);
+    /// ```
+    fn pipe<X>(self, next: X) -> Composed<Self, X>
+    where
+        Self: Sized,
+        X: Transducer<Self::Output>,
+    {
+        Composed::new(self, next)
+    }
+}
+
+impl<F, G, A> Transducer<A> for Composed<F, G>
+where
+    F: Transducer<A>,
+    G: Transducer<F::Output>,
+{
+    type Output = G::Output;
+
+    fn apply<Acc, R>(self, reducer: R) -> impl Reducer<Acc, A>
+    where
+        R: Reducer<Acc, Self::Output>,
+    {
+        self.first.apply(self.second.apply(reducer))
+    }
+}
+
+/// A transducer mapping every item, created by [`mapping`].
+#[derive(Debug, Clone, Copy)]
+pub struct Mapping<F> {
+    f: F,
+}
+
+/// Creates a transducer mapping every item with the provided function or
+/// closure.
+pub fn mapping<F, A, B>(f: F) -> Mapping<F>
+where
+    F: FnMut(A) -> B,
+{
+    Mapping { f }
+}
+
+struct MappingReducer<F, R> {
+    f: F,
+    reducer: R,
+}
+
+impl<F, R, Acc, A, B> Reducer<Acc, A> for MappingReducer<F, R>
+where
+    F: FnMut(A) -> B,
+    R: Reducer<Acc, B>,
+{
+    fn step(&mut self, acc: Acc, item: A) -> Step<Acc> {
+        self.reducer.step(acc, (self.f)(item))
+    }
+
+    fn complete(&mut self, acc: Acc) -> Acc {
+        self.reducer.complete(acc)
+    }
+
+    fn is_done(&self) -> bool {
+        self.reducer.is_done()
+    }
+}
+
+impl<F, A, B> Transducer<A> for Mapping<F>
+where
+    F: FnMut(A) -> B,
+{
+    type Output = B;
+
+    fn apply<Acc, R>(self, reducer: R) -> impl Reducer<Acc, A>
+    where
+        R: Reducer<Acc, B>,
+    {
+        MappingReducer { f: self.f, reducer }
+    }
+}
+
+/// A transducer keeping the items matching a predicate, created by
+/// [`filtering`].
+#[derive(Debug, Clone, Copy)]
+pub struct Filtering<P> {
+    pred: P,
+}
+
+/// Creates a transducer keeping only the items for which the predicate holds.
+pub fn filtering<P, A>(pred: P) -> Filtering<P>
+where
+    P: FnMut(&A) -> bool,
+{
+    Filtering { pred }
+}
+
+struct FilteringReducer<P, R> {
+    pred: P,
+    reducer: R,
+}
+
+impl<P, R, Acc, A> Reducer<Acc, A> for FilteringReducer<P, R>
+where
+    P: FnMut(&A) -> bool,
+    R: Reducer<Acc, A>,
+{
+    fn step(&mut self, acc: Acc, item: A) -> Step<Acc> {
+        if (self.pred)(&item) {
+            self.reducer.step(acc, item)
+        } else {
+            Step::Continue(acc)
+        }
+    }
+
+    fn complete(&mut self, acc: Acc) -> Acc {
+        self.reducer.complete(acc)
+    }
+
+    fn is_done(&self) -> bool {
+        self.reducer.is_done()
+    }
+}
+
+impl<P, A> Transducer<A> for Filtering<P>
+where
+    P: FnMut(&A) -> bool,
+{
+    type Output = A;
+
+    fn apply<Acc, R>(self, reducer: R) -> impl Reducer<Acc, A>
+    where
+        R: Reducer<Acc, A>,
+    {
+        FilteringReducer {
+            pred: self.pred,
+            reducer,
+        }
+    }
+}
+
+/// A transducer passing on at most a fixed number of items, created by
+/// [`taking`].
+#[derive(Debug)]
+pub struct Taking<A> {
+    n: usize,
+    _item: PhantomData<fn(A) -> A>,
+}
+
+impl<A> Clone for Taking<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for Taking<A> {}
+
+/// Creates a transducer passing on at most the first `n` items, then
+/// stopping the process early.
+pub fn taking<A>(n: usize) -> Taking<A> {
+    Taking {
+        n,
+        _item: PhantomData,
+    }
+}
+
+struct TakingReducer<R> {
+    remaining: usize,
+    reducer: R,
+}
+
+impl<R, Acc, A> Reducer<Acc, A> for TakingReducer<R>
+where
+    R: Reducer<Acc, A>,
+{
+    fn step(&mut self, acc: Acc, item: A) -> Step<Acc> {
+        if self.remaining == 0 {
+            return Step::Done(acc);
+        }
+        self.remaining -= 1;
+        match self.reducer.step(acc, item) {
+            Step::Continue(acc) if self.remaining == 0 => Step::Done(acc),
+            step => step,
+        }
+    }
+
+    fn complete(&mut self, acc: Acc) -> Acc {
+        self.reducer.complete(acc)
+    }
+
+    fn is_done(&self) -> bool {
+        self.remaining == 0 || self.reducer.is_done()
+    }
+}
+
+impl<A> Transducer<A> for Taking<A> {
+    type Output = A;
+
+    fn apply<Acc, R>(self, reducer: R) -> impl Reducer<Acc, A>
+    where
+        R: Reducer<Acc, A>,
+    {
+        TakingReducer {
+            remaining: self.n,
+            reducer,
+        }
+    }
+}
+
+/// A transducer grouping items into partitions, created by [`partitioning`].
+#[derive(Debug)]
+pub struct Partitioning<A> {
+    size: usize,
+    _item: PhantomData<fn(A) -> Vec<A>>,
+}
+
+impl<A> Clone for Partitioning<A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<A> Copy for Partitioning<A> {}
+
+/// Creates a transducer grouping items into [`Vec`]s of `size` items. The
+/// last partition holds the remaining items, and may be smaller.
+///
+/// ```
+/// # use pipe::*;
+/// let rows = transduce(1..=5, partitioning(2), Vec::new(), |mut rows, row| {
+///     rows.push(row);
+///     rows
+/// });
+/// assert_eq!(rows, [vec![1, 2], vec![3, 4], vec![5]]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if `size` is `0`.
+pub fn partitioning<A>(size: usize) -> Partitioning<A> {
+    assert!(size > 0, "partition size must be greater than 0");
+    Partitioning {
+        size,
+        _item: PhantomData,
+    }
+}
+
+struct PartitioningReducer<A, R> {
+    size: usize,
+    partition: Vec<A>,
+    reducer: R,
+}
+
+impl<R, Acc, A> Reducer<Acc, A> for PartitioningReducer<A, R>
+where
+    R: Reducer<Acc, Vec<A>>,
+{
+    fn step(&mut self, acc: Acc, item: A) -> Step<Acc> {
+        self.partition.push(item);
+        if self.partition.len() < self.size {
+            return Step::Continue(acc);
+        }
+        let partition = mem::replace(&mut self.partition, Vec::with_capacity(self.size));
+        self.reducer.step(acc, partition)
+    }
+
+    fn complete(&mut self, acc: Acc) -> Acc {
+        let acc = if self.partition.is_empty() {
+            acc
+        } else {
+            let partition = mem::take(&mut self.partition);
+            self.reducer.step(acc, partition).into_inner()
+        };
+        self.reducer.complete(acc)
+    }
+
+    fn is_done(&self) -> bool {
+        self.reducer.is_done()
+    }
+}
+
+impl<A> Transducer<A> for Partitioning<A> {
+    type Output = Vec<A>;
+
+    fn apply<Acc, R>(self, reducer: R) -> impl Reducer<Acc, A>
+    where
+        R: Reducer<Acc, Vec<A>>,
+    {
+        PartitioningReducer {
+            size: self.size,
+            partition: Vec::with_capacity(self.size),
+            reducer,
+        }
+    }
+}
+
+/// Runs a transducer over the items of an iterable source, accumulating its
+/// output into `init` with the reducing function `f`.
+///
+/// Any [`IntoIterator`] can be used as a source, including the receiving end
+/// of a [channel](std::sync::mpsc). If the transducer stops early, no more
+/// items are taken from the source, and a transducer that accepts no items
+/// at all, such as `taking(0)`, takes none.
+///
+/// ```
+/// # use pipe::*;
+/// use std::sync::mpsc;
+///
+/// let (tx, rx) = mpsc::channel();
+/// for word in ["pipe", "a", "reducer", "to", "channel"] {
+///     tx.send(word).unwrap();
+/// }
+///
+/// let xform = filtering(|w: &&str| w.len() > 2).pipe(taking(2));
+/// let longest = transduce(&rx, xform, 0, |max, w: &str| max.max(w.len()));
+/// assert_eq!(longest, 7);
+/// assert_eq!(rx.recv(), Ok("to"));
+/// ```
+pub fn transduce<I, X, Acc, F>(source: I, xform: X, init: Acc, f: F) -> Acc
+where
+    I: IntoIterator,
+    X: Transducer<I::Item>,
+    F: FnMut(Acc, X::Output) -> Acc,
+{
+    let mut reducer = xform.apply(f);
+    let mut acc = init;
+    let mut items = source.into_iter();
+    while !reducer.is_done() {
+        let Some(item) = items.next() else {
+            break;
+        };
+        match reducer.step(acc, item) {
+            Step::Continue(next) => acc = next,
+            Step::Done(done) => {
+                acc = done;
+                break;
+            }
+        }
+    }
+    reducer.complete(acc)
+}
+
+/// Runs a transducer over an asynchronous source, accumulating its output into
+/// `init` with the reducing function `f`.
+///
+/// The source is called for each item, and the process ends once it returns
+/// `None` or the transducer stops early, without calling the source again.
+/// This does not depend on any particular runtime, so the source can wrap an
+/// async channel, a stream or any other asynchronous producer.
+///
+/// ```
+/// # use pipe::*;
+/// # use pipe::__block_on as block_on;
+/// async fn fetch_page(page: u32) -> Option<Vec<u32>> {
+///     (page < 3).then(|| vec![page * 10, page * 10 + 1])
+/// }
+///
+/// let mut page = 0;
+/// let next_page = async || {
+///     page += 1;
+///     fetch_page(page - 1).await
+/// };
+/// let xform = mapping(|ids: Vec<u32>| ids.len());
+/// let total = block_on(transduce_async(next_page, xform, 0, |acc, n| acc + n));
+/// assert_eq!(total, 6);
+/// ```
+pub async fn transduce_async<S, T, X, Acc, F>(mut source: S, xform: X, init: Acc, f: F) -> Acc
+where
+    S: AsyncFnMut() -> Option<T>,
+    X: Transducer<T>,
+    F: FnMut(Acc, X::Output) -> Acc,
+{
+    let mut reducer = xform.apply(f);
+    let mut acc = init;
+    while !reducer.is_done() {
+        let Some(item) = source().await else {
+            break;
+        };
+        match reducer.step(acc, item) {
+            Step::Continue(next) => acc = next,
+            Step::Done(done) => {
+                acc = done;
+                break;
+            }
+        }
+    }
+    reducer.complete(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{block_on, YieldNow};
+    use crate::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn push<T>(mut v: Vec<T>, item: T) -> Vec<T> {
+        v.push(item);
+        v
+    }
+
+    #[test]
+    fn test_transduce_composition() {
+        let xform = mapping(|s: &str| s.trim())
+            .pipe(filtering(|s: &&str| !s.is_empty()))
+            .pipe(mapping(str::to_uppercase))
+            .pipe(partitioning(2));
+        let rows = transduce([" a", "", "b ", "c", " "], xform, Vec::new(), push);
+        assert_eq!(rows, [vec!["A", "B"], vec!["C"]]);
+    }
+
+    #[test]
+    fn test_transduce_early_termination() {
+        let mut pulled = 0;
+        let source = (1..).inspect(|_| pulled += 1);
+        let firsts = transduce(source, partitioning(3).pipe(taking(2)), Vec::new(), push);
+        assert_eq!(firsts, [vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(pulled, 6);
+
+        let rest = transduce(1.., taking(5).pipe(partitioning(2)), Vec::new(), push);
+        assert_eq!(rest, [vec![1, 2], vec![3, 4], vec![5]]);
+
+        let mut pulled = 0;
+        let source = [1, 2].into_iter().inspect(|_| pulled += 1);
+        let xform = mapping(|n: i32| n * 2).pipe(taking(0));
+        let none = transduce(source, xform, Vec::new(), push);
+        assert!(none.is_empty());
+        assert_eq!(pulled, 0);
+    }
+
+    #[test]
+    fn test_transduce_async() {
+        let mut items = vec![5, 4, 3, 2, 1];
+        let mut pulled = 0;
+        let source = async || {
+            YieldNow(false).await;
+            pulled += 1;
+            items.pop()
+        };
+        let xform = mapping(|n: i32| n * 10).pipe(taking(2));
+        let sum = block_on(transduce_async(source, xform, 0, |acc, n| acc + n));
+        assert_eq!(sum, 30);
+        assert_eq!(pulled, 2);
+
+        let mut pulled = 0;
+        let source = async || {
+            pulled += 1;
+            Some(1)
+        };
+        let none = block_on(transduce_async(source, taking(0), Vec::new(), push));
+        assert!(none.is_empty());
+        assert_eq!(pulled, 0);
+    }
+
+    #[test]
+    fn test_transduce_channel() {
+        let (tx, rx) = mpsc::channel();
+        let producer = thread::spawn(move || {
+            for n in 0..100 {
+                if tx.send(n).is_err() {
+                    break;
+                }
+            }
+        });
+        let xform = filtering(|n: &i32| n % 3 == 0).pipe(taking(4));
+        let multiples = transduce(rx, xform, Vec::new(), push);
+        assert_eq!(multiples, [0, 3, 6, 9]);
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_transduce_channel_taking_none() {
+        let (tx, rx) = mpsc::channel();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        let none = transduce(&rx, taking(0), Vec::new(), push);
+        assert!(none.is_empty());
+        assert_eq!(rx.recv(), Ok(1));
+    }
+
+    #[test]
+    fn test_step() {
+        assert!(Step::Done(1).is_done());
+        assert!(!Step::Continue(1).is_done());
+        assert_eq!(Step::Continue(2).into_inner(), 2);
+    }
+}
@@ -0,0 +1,142 @@
+/// The `TryPipe` trait composes fallible pipelines, where each stage returns a
+/// `Result`. The pipeline short-circuits on the first error, so later stages
+/// only ever see successful values.
+///
+/// Errors returned by later stages are converted into the pipeline's error
+/// type through `From`, the same way the `?` operator converts them. Use
+/// [`TryPipe::err_into`] to widen the error type of the first stage when later
+/// stages fail with different error types.
+///
+/// When in scope, this trait is implemented for all types implementing
+/// `FnOnce(A) -> Result<B, E>`, for any types `A`, `B` and `E`.
+pub trait TryPipe<A, B, E> {
+    /// Wraps a fallible function or closure inside the currently constructed
+    /// pipeline. The stage is only called if every previous stage succeeded.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let parse_even = pipe(|s: &str| s.parse::<i32>().map_err(|e| e.to_string()))
+    ///     .try_pipe(|n| if n % 2 == 0 { Ok(n) } else { Err(format!("{n} is odd")) });
+    /// assert_eq!(parse_even("7"), Err("7 is odd".to_owned()));
+    /// ```
+    fn try_pipe<F, C, E2>(self, f: F) -> impl FnOnce(A) -> Result<C, E>
+    where
+        F: FnOnce(B) -> Result<C, E2>,
+        E: From<E2>;
+
+    /// Wraps an infallible function or closure inside the currently
+    /// constructed pipeline. The stage is only called if every previous stage
+    /// succeeded.
+    ///
+    /// ```
+    /// # use pipe::*;
+    /// let parse_double = pipe(|s: &str| s.parse::<i32>()).ok_pipe(|n| n * 2);
+    /// assert_eq!(parse_double("21"), Ok(42));
+    /// ```
+    fn ok_pipe<F, C>(self, f: F) -> impl FnOnce(A) -> Result<C, E>
+    where
+        F: FnOnce(B) -> C;
+
+    /// Converts the error type of the currently constructed pipeline, so that
+    /// later stages may fail with any error type convertible into `E2`.
+    fn err_into<E2>(self) -> impl FnOnce(A) -> Result<B, E2>
+    where
+        E: Into<E2>;
+}
+
+impl<F1, A, B, E> TryPipe<A, B, E> for F1
+where
+    F1: FnOnce(A) -> Result<B, E>,
+{
+    fn try_pipe<F2, C, E2>(self, f: F2) -> impl FnOnce(A) -> Result<C, E>
+    where
+        F2: FnOnce(B) -> Result<C, E2>,
+        E: From<E2>,
+    {
+        |a| Ok(f(self(a)?)?)
+    }
+
+    fn ok_pipe<F2, C>(self, f: F2) -> impl FnOnce(A) -> Result<C, E>
+    where
+        F2: FnOnce(B) -> C,
+    {
+        |a| self(a).map(f)
+    }
+
+    fn err_into<E2>(self) -> impl FnOnce(A) -> Result<B, E2>
+    where
+        E: Into<E2>,
+    {
+        |a| self(a).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::num::ParseIntError;
+
+    #[derive(Debug, PartialEq)]
+    enum ConfigError {
+        Parse(ParseIntError),
+        OutOfRange(i32),
+    }
+
+    impl From<ParseIntError> for ConfigError {
+        fn from(err: ParseIntError) -> Self {
+            Self::Parse(err)
+        }
+    }
+
+    struct OutOfRange(i32);
+
+    impl From<OutOfRange> for ConfigError {
+        fn from(OutOfRange(n): OutOfRange) -> Self {
+            Self::OutOfRange(n)
+        }
+    }
+
+    #[test]
+    fn test_try_pipe_short_circuits() {
+        let mut later_stage_called = false;
+        let parse = pipe(|s: &str| s.parse::<i32>())
+            .try_pipe(|n| "2".parse::<i32>().map(|m| n * m))
+            .ok_pipe(|n| {
+                later_stage_called = true;
+                n + 1
+            });
+        assert!(parse("foo").is_err());
+        assert!(!later_stage_called);
+    }
+
+    #[test]
+    fn test_try_pipe_mixed_errors() {
+        let parse_port = || {
+            pipe(|s: &str| s.trim().parse::<i32>())
+                .err_into::<ConfigError>()
+                .try_pipe(|n| {
+                    if (1..=65535).contains(&n) {
+                        Ok(n)
+                    } else {
+                        Err(OutOfRange(n))
+                    }
+                })
+                .ok_pipe(|n| n as u16)
+        };
+        assert_eq!(parse_port()(" 8080 "), Ok(8080));
+        assert_eq!(parse_port()("0"), Err(ConfigError::OutOfRange(0)));
+        assert!(matches!(parse_port()("port"), Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn test_try_pipe_macro() {
+        let parse_port = pipe! { try this: &str => ConfigError;
+               this.trim().parse::<i32>()?
+            => if (1..=65535).contains(&this) { this } else { Err(OutOfRange(this))? }
+            => this as u16
+        };
+        assert_eq!(parse_port(" 8080 "), Ok(8080));
+        assert_eq!(parse_port("70000"), Err(ConfigError::OutOfRange(70000)));
+        assert!(matches!(parse_port("port"), Err(ConfigError::Parse(_))));
+    }
+}
@@ -0,0 +1,5 @@
+use pipe::compose;
+
+fn main() {
+    let _ = compose![];
+}
@@ -0,0 +1,13 @@
+use pipe::compose;
+
+fn parse(s: &str) -> i32 {
+    s.parse().unwrap()
+}
+
+fn shout(s: String) -> String {
+    s.to_uppercase()
+}
+
+fn main() {
+    let _ = compose![parse, shout, |s: String| s.len()];
+}
@@ -0,0 +1,9 @@
+use pipe::Pipe;
+
+fn run<P: Pipe<&'static str, usize, usize>>(pipeline: P) {
+    let _ = pipeline.pipe(|n| n + 1);
+}
+
+fn main() {
+    run("not a pipeline");
+}
@@ -0,0 +1,5 @@
+use pipe::pipe;
+
+fn main() {
+    let _ = pipe! { pair: (i32, i32); |(a, b)| => a + b };
+}
@@ -0,0 +1,5 @@
+use pipe::pipe;
+
+fn main() {
+    let _ = pipe! { 5 => this + 1 };
+}
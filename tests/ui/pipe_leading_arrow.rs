@@ -0,0 +1,5 @@
+use pipe::pipe;
+
+fn main() {
+    let _ = pipe! { this: i32; => this + 1 };
+}
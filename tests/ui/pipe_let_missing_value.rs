@@ -0,0 +1,5 @@
+use pipe::pipe;
+
+fn main() {
+    let _ = pipe! { this: (i32, i32); let (a, b): (i32, i32) => a + b };
+}
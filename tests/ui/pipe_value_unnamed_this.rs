@@ -0,0 +1,5 @@
+use pipe::pipe_value;
+
+fn main() {
+    let _ = pipe_value!("input" => str::trim => this.len());
+}
@@ -0,0 +1,10 @@
+use pipe::Pipeline;
+
+fn main() {
+    let mut total = 0;
+    let pipeline = Pipeline::new(|n: i32| n * 2).then_mut(|n| {
+        total += n;
+        total
+    });
+    pipeline.call(1);
+}
@@ -0,0 +1,5 @@
+use pipe::Pipeline;
+
+fn main() {
+    let _ = Pipeline::<i32, _>::new("not a stage");
+}
@@ -0,0 +1,9 @@
+use pipe::*;
+
+fn shout(s: String) -> String {
+    s.to_uppercase()
+}
+
+fn main() {
+    let _ = pipe(|s: &str| s.len()).pipe(shout);
+}
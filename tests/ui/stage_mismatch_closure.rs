@@ -0,0 +1,5 @@
+use pipe::*;
+
+fn main() {
+    let _ = pipe(|s: &str| s.len()).pipe(|s: String| s.to_uppercase());
+}
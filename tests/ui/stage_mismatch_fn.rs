@@ -0,0 +1,9 @@
+use pipe::*;
+
+fn shout(s: String) -> String {
+    s.to_uppercase()
+}
+
+fn main() {
+    let _ = pipe_fn(|s: &str| s.len()).pipe_fn(shout);
+}